@@ -0,0 +1,530 @@
+//! The local `sui-packages` bytecode dataset: artifact lookup, metadata and linkage.
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use rayon::prelude::*;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use move_binary_format::CompiledModule;
+
+use crate::inventory::InventoryOptions;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum MvrNetwork {
+    Mainnet,
+    Testnet,
+    Devnet,
+}
+
+impl MvrNetwork {
+    /// `sui-packages/packages/<subdir>` holding this network's dataset.
+    pub fn dataset_subdir(self) -> &'static str {
+        match self {
+            MvrNetwork::Mainnet => "mainnet_most_used",
+            MvrNetwork::Testnet => "testnet_most_used",
+            MvrNetwork::Devnet => "devnet_most_used",
+        }
+    }
+
+    pub fn default_rpc_url(self) -> &'static str {
+        match self {
+            MvrNetwork::Mainnet => "https://fullnode.mainnet.sui.io:443",
+            MvrNetwork::Testnet => "https://fullnode.testnet.sui.io:443",
+            MvrNetwork::Devnet => "https://fullnode.devnet.sui.io:443",
+        }
+    }
+
+    pub fn default_graphql_url(self) -> &'static str {
+        match self {
+            MvrNetwork::Mainnet => "https://sui-mainnet.mystenlabs.com/graphql",
+            MvrNetwork::Testnet => "https://sui-testnet.mystenlabs.com/graphql",
+            MvrNetwork::Devnet => "https://sui-devnet.mystenlabs.com/graphql",
+        }
+    }
+
+    /// Base URL of the MVR name resolver; devnet has none.
+    pub fn mvr_api_url(self) -> Option<&'static str> {
+        match self {
+            MvrNetwork::Mainnet => Some("https://mainnet.mvr.mystenlabs.com"),
+            MvrNetwork::Testnet => Some("https://testnet.mvr.mystenlabs.com"),
+            MvrNetwork::Devnet => None,
+        }
+    }
+
+    pub fn mvr_package_info_field(self) -> &'static str {
+        match self {
+            MvrNetwork::Mainnet => "mainnet_package_info_id",
+            MvrNetwork::Testnet => "testnet_package_info_id",
+            MvrNetwork::Devnet => "devnet_package_info_id",
+        }
+    }
+}
+
+/// Where the local dataset lives and how strictly it is read.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DatasetOptions {
+    /// Root of the `sui-packages` checkout.
+    pub root: PathBuf,
+    /// Network whose `packages/<network>_most_used` entries are read.
+    pub network: MvrNetwork,
+    /// Package id -> original id, keyed by `normalize_package_id`; wins over the dataset's
+    /// metadata.json.
+    pub original_id_overrides: BTreeMap<String, String>,
+    /// A module that fails to deserialize is logged and skipped instead of failing its
+    /// package.
+    pub skip_bad_modules: bool,
+    /// Dataset inconsistencies that are otherwise only logged become errors.
+    pub strict: bool,
+}
+
+impl Default for DatasetOptions {
+    fn default() -> Self {
+        Self {
+            root: default_dataset_root(),
+            network: MvrNetwork::Mainnet,
+            original_id_overrides: BTreeMap::new(),
+            skip_bad_modules: false,
+            strict: false,
+        }
+    }
+}
+
+/// Deserialize one `.mv` file. Under `skip_bad_modules` a module that fails to deserialize
+/// is logged and yields `None` instead of an error.
+fn deserialize_module_file(
+    dataset: &DatasetOptions,
+    path: &Path,
+) -> Result<Option<CompiledModule>> {
+    let bytes = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+    match CompiledModule::deserialize_with_defaults(&bytes) {
+        Ok(module) => Ok(Some(module)),
+        Err(e) if dataset.skip_bad_modules => {
+            tracing::warn!(path = %path.display(), error = %e, "skipping bad module");
+            Ok(None)
+        }
+        Err(e) => Err(e).with_context(|| format!("deserialize {}", path.display())),
+    }
+}
+
+/// All modules of one package share its self address; several addresses in one
+/// `bytecode_modules` dir mean a corrupt entry or dependencies mixed in. Warns, or errors
+/// under `strict`.
+fn check_single_address(
+    dataset: &DatasetOptions,
+    bytecode_modules_dir: &Path,
+    modules: &[CompiledModule],
+) -> Result<()> {
+    let addresses: BTreeSet<String> = modules
+        .iter()
+        .map(|m| m.self_id().address().to_hex_literal())
+        .collect();
+    if addresses.len() <= 1 {
+        return Ok(());
+    }
+    if dataset.strict {
+        return Err(anyhow!(
+            "{} holds modules of {} addresses: {:?}",
+            bytecode_modules_dir.display(),
+            addresses.len(),
+            addresses
+        ));
+    }
+    tracing::warn!(
+        dir = %bytecode_modules_dir.display(),
+        ?addresses,
+        "bytecode_modules holds modules of several addresses"
+    );
+    Ok(())
+}
+
+/// Modules loaded from one `bytecode_modules` dir.
+pub struct BytecodeModules {
+    pub modules: Vec<CompiledModule>,
+    /// `.mv` files skipped under `skip_bad_modules`.
+    pub skipped: usize,
+    /// On-chain package version, when the source records it.
+    pub version: Option<u64>,
+}
+
+/// Deserialize every `.mv` file in `bytecode_modules_dir` on the global rayon pool (sized
+/// by `--jobs`), returning the modules in file-name order.
+pub fn load_bytecode_modules(
+    dataset: &DatasetOptions,
+    bytecode_modules_dir: &Path,
+) -> Result<BytecodeModules> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(bytecode_modules_dir)
+        .with_context(|| format!("read_dir {}", bytecode_modules_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("mv") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    let loaded: Vec<Option<CompiledModule>> = paths
+        .par_iter()
+        .map(|path| deserialize_module_file(dataset, path))
+        .collect::<Result<_>>()?;
+    let skipped = loaded.iter().filter(|m| m.is_none()).count();
+    let modules: Vec<CompiledModule> = loaded.into_iter().flatten().collect();
+    check_single_address(dataset, bytecode_modules_dir, &modules)?;
+    Ok(BytecodeModules {
+        modules,
+        skipped,
+        version: None,
+    })
+}
+
+pub fn load_compiled_modules_from_bytecode_modules_dir(
+    dataset: &DatasetOptions,
+    bytecode_modules_dir: &Path,
+) -> Result<Vec<CompiledModule>> {
+    Ok(load_bytecode_modules(dataset, bytecode_modules_dir)?.modules)
+}
+
+pub fn parse_linkage_deps_from_artifact_dir(artifact_dir: &Path) -> Result<Vec<String>> {
+    let bcs_path = artifact_dir.join("bcs.json");
+    let bcs_text =
+        fs::read_to_string(&bcs_path).with_context(|| format!("read {}", bcs_path.display()))?;
+    let bcs_value: Value = serde_json::from_str(&bcs_text)
+        .with_context(|| format!("parse json {}", bcs_path.display()))?;
+
+    let mut deps = Vec::new();
+    if let Some(linkage) = bcs_value.get("linkageTable").and_then(Value::as_object) {
+        for (dep_id, _info) in linkage {
+            deps.push(dep_id.to_string());
+        }
+    }
+    Ok(deps)
+}
+
+/// The package version in the artifact dir's `bcs.json`, if it has one.
+pub fn read_package_version_from_artifact_dir(artifact_dir: &Path) -> Option<u64> {
+    let text = fs::read_to_string(artifact_dir.join("bcs.json")).ok()?;
+    let value: Value = serde_json::from_str(&text).ok()?;
+    match value.get("version")? {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// The package's modules from the local dataset, or `None` when it has no artifact dir.
+pub fn try_load_local_bytecode_modules(
+    dataset: &DatasetOptions,
+    package_id: &str,
+) -> Result<Option<BytecodeModules>> {
+    let artifact_dir = match sui_packages_artifact_dir_for_package_id(dataset, package_id)
+        .and_then(|p| p.canonicalize().map_err(|e| anyhow!(e)))
+    {
+        Ok(p) => p,
+        Err(_) => return Ok(None),
+    };
+    let bytecode_dir = artifact_dir.join("bytecode_modules");
+    let mut mods = load_bytecode_modules(dataset, &bytecode_dir)
+        .with_context(|| format!("load local modules {}", package_id))?;
+    mods.version = read_package_version_from_artifact_dir(&artifact_dir);
+    Ok(Some(mods))
+}
+
+pub fn try_load_local_modules_for_package(
+    dataset: &DatasetOptions,
+    package_id: &str,
+) -> Result<Option<Vec<CompiledModule>>> {
+    Ok(try_load_local_bytecode_modules(dataset, package_id)?.map(|m| m.modules))
+}
+
+pub fn extract_module_names_from_bytecode_modules_dir(
+    dataset: &DatasetOptions,
+    inventory: &InventoryOptions,
+    bytecode_modules_dir: &Path,
+) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(bytecode_modules_dir)
+        .with_context(|| format!("read_dir {}", bytecode_modules_dir.display()))?
+    {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("mv") {
+            continue;
+        }
+        let Some(module) = deserialize_module_file(dataset, &path)? else {
+            continue;
+        };
+        let name = module.self_id().name().to_string();
+        if inventory.module_selected(&name) {
+            names.push(name);
+        }
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Canonical `0x` + 64 lowercase hex form of a package id, for use as a lookup key.
+pub fn normalize_package_id(id: &str) -> String {
+    let id = id.trim();
+    let hex = id.strip_prefix("0x").unwrap_or(id);
+    format!("0x{:0>64}", hex.to_ascii_lowercase())
+}
+
+/// Read a JSON object keyed by package id, normalizing the keys.
+pub fn read_package_keyed_json<T: serde::de::DeserializeOwned>(
+    path: &Path,
+) -> Result<BTreeMap<String, T>> {
+    let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let map: BTreeMap<String, T> =
+        serde_json::from_str(&text).with_context(|| format!("parse {}", path.display()))?;
+    Ok(map
+        .into_iter()
+        .map(|(k, v)| (normalize_package_id(&k), v))
+        .collect())
+}
+
+/// Dataset root used when none is given: `$SUI_PACKAGES_DIR`, else `../sui-packages`
+/// (relative to cwd).
+pub fn default_dataset_root() -> PathBuf {
+    std::env::var_os("SUI_PACKAGES_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("../sui-packages"))
+}
+
+/// Artifact dir of `package_id` in the dataset. When the dataset has no entry for the id
+/// itself, the ids `--original-id-overrides` or dataset metadata tie it to are tried: its
+/// original id, then other upgraded versions of that original. The unresolved path is
+/// returned if none exist.
+pub fn sui_packages_artifact_dir_for_package_id(
+    dataset: &DatasetOptions,
+    package_id: &str,
+) -> Result<PathBuf> {
+    let sui_packages_dir = dataset.root.as_path();
+    let dir = artifact_dir_in_sui_packages(sui_packages_dir, dataset.network, package_id)?;
+    if dir.exists() {
+        return Ok(dir);
+    }
+    for candidate in related_package_ids(dataset, package_id) {
+        let alt = artifact_dir_in_sui_packages(sui_packages_dir, dataset.network, &candidate)?;
+        if alt.exists() {
+            tracing::debug!(%package_id, resolved = %candidate, "local artifacts found under related id");
+            return Ok(alt);
+        }
+    }
+    Ok(dir)
+}
+
+/// Original-id links between dataset packages, from `--original-id-overrides` and every
+/// entry's metadata.json (overrides win), indexed both ways. Built once per
+/// `DatasetOptions`, on the first artifact lookup under them that misses.
+struct RelatedIds {
+    /// Package id -> original id, for upgraded packages.
+    original_of: BTreeMap<String, String>,
+    /// Original id -> ids of its upgraded versions.
+    upgrades_of: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Per-`DatasetOptions` slot for a `RelatedIds` index, filled on first use.
+type RelatedIdsCell = Arc<std::sync::OnceLock<Arc<RelatedIds>>>;
+
+fn related_ids(dataset: &DatasetOptions) -> Arc<RelatedIds> {
+    static RELATED: std::sync::OnceLock<Mutex<BTreeMap<DatasetOptions, RelatedIdsCell>>> =
+        std::sync::OnceLock::new();
+    // The map lock only hands out the cell; the scan runs outside it, so lookups under other
+    // options don't wait on it and concurrent first lookups still build the index once.
+    let cell = RELATED
+        .get_or_init(Default::default)
+        .lock()
+        .expect("related ids lock")
+        .entry(dataset.clone())
+        .or_default()
+        .clone();
+    Arc::clone(cell.get_or_init(|| Arc::new(build_related_ids(dataset))))
+}
+
+/// Scan every dataset entry's metadata.json (in parallel) and merge in the overrides.
+fn build_related_ids(dataset: &DatasetOptions) -> RelatedIds {
+    let from_metadata: Vec<(String, String)> = iter_dataset_package_ids(dataset, usize::MAX)
+        .unwrap_or_default()
+        .into_par_iter()
+        .filter_map(|id| {
+            let dir = artifact_dir_in_sui_packages(&dataset.root, dataset.network, &id).ok()?;
+            let data = fs::read_to_string(dir.join("metadata.json")).ok()?;
+            let v: Value = serde_json::from_str(&data).ok()?;
+            let original = v.get("originalPackageId")?.as_str()?;
+            Some((normalize_package_id(&id), normalize_package_id(original)))
+        })
+        .collect();
+    let mut original_of: BTreeMap<String, String> = from_metadata.into_iter().collect();
+    original_of.extend(
+        dataset
+            .original_id_overrides
+            .iter()
+            .map(|(id, original)| (id.clone(), normalize_package_id(original))),
+    );
+    original_of.retain(|id, original| id != original);
+    let mut upgrades_of: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (id, original) in &original_of {
+        upgrades_of
+            .entry(original.clone())
+            .or_default()
+            .insert(id.clone());
+    }
+    RelatedIds {
+        original_of,
+        upgrades_of,
+    }
+}
+
+/// Build the related-id index for `dataset` now rather than on the first artifact lookup
+/// that misses. The scan reads every entry's metadata.json, so async callers run this in
+/// `spawn_blocking` before fanning packages out.
+pub fn prepare_related_ids(dataset: &DatasetOptions) {
+    related_ids(dataset);
+}
+
+/// Ids linked to `package_id` through its original id, original id first, then the
+/// other upgraded versions of that original.
+fn related_package_ids(dataset: &DatasetOptions, package_id: &str) -> Vec<String> {
+    let index = related_ids(dataset);
+    let id = normalize_package_id(package_id);
+    let original = index.original_of.get(&id).unwrap_or(&id);
+    let mut related: Vec<String> = Vec::new();
+    if *original != id {
+        related.push(original.clone());
+    }
+    related.extend(
+        index
+            .upgrades_of
+            .get(original)
+            .into_iter()
+            .flatten()
+            .filter(|upgraded| **upgraded != id)
+            .cloned(),
+    );
+    related
+}
+
+/// Artifact dir of `package_id` under an explicit `sui-packages` checkout.
+pub fn artifact_dir_in_sui_packages(
+    sui_packages_dir: &Path,
+    network: MvrNetwork,
+    package_id: &str,
+) -> Result<PathBuf> {
+    let package_id = package_id.strip_prefix("0x").unwrap_or(package_id);
+    // Left-pad with zeros to 64 hex chars if needed (handles short addresses like 0x2 or 63-char ids).
+    let package_id = format!("{:0>64}", package_id);
+    if package_id.len() != 64 || !package_id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!(
+            "expected 64-hex package id (optionally 0x-prefixed), got: {}",
+            package_id
+        ));
+    }
+
+    let prefix = &package_id[0..2];
+    let entry_name = &package_id[2..];
+
+    // Dataset layout is `<dataset root>/packages/<network>_most_used/0x??/<suffix62>`
+    Ok(sui_packages_dir
+        .join("packages")
+        .join(network.dataset_subdir())
+        .join(format!("0x{}", prefix))
+        .join(entry_name))
+}
+
+/// Read a string field from the package's dataset metadata.json, if both exist.
+pub fn read_metadata_string_field(
+    dataset: &DatasetOptions,
+    package_id: &str,
+    key: &str,
+) -> Result<Option<String>> {
+    let artifact_dir = sui_packages_artifact_dir_for_package_id(dataset, package_id)?
+        .canonicalize()
+        .ok();
+    let artifact_dir = match artifact_dir {
+        Some(d) => d,
+        None => return Ok(None),
+    };
+    let meta_path = artifact_dir.join("metadata.json");
+    if !meta_path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(&meta_path)?;
+    let v: serde_json::Value = serde_json::from_str(&data)?;
+    Ok(v.get(key).and_then(|v| v.as_str()).map(|s| s.to_string()))
+}
+
+/// Read the originalPackageId from metadata.json if present. Falls back to the passed id.
+fn read_original_package_id_from_metadata(
+    dataset: &DatasetOptions,
+    package_id: &str,
+) -> Result<String> {
+    Ok(
+        read_metadata_string_field(dataset, package_id, "originalPackageId")?
+            .unwrap_or_else(|| package_id.to_string()),
+    )
+}
+
+/// Original (pre-upgrade) package id: an `--original-id-overrides` entry wins over
+/// metadata.json, which wins over the package id itself.
+pub fn resolve_original_package_id(dataset: &DatasetOptions, package_id: &str) -> String {
+    if let Some(id) = dataset
+        .original_id_overrides
+        .get(&normalize_package_id(package_id))
+    {
+        return id.clone();
+    }
+    read_original_package_id_from_metadata(dataset, package_id)
+        .unwrap_or_else(|_| package_id.to_string())
+}
+
+pub fn iter_dataset_package_ids(dataset: &DatasetOptions, limit: usize) -> Result<Vec<String>> {
+    let dataset_root = dataset
+        .root
+        .join("packages")
+        .join(dataset.network.dataset_subdir());
+
+    let mut entries: Vec<(String, String)> = Vec::new();
+    for prefix_dir in fs::read_dir(&dataset_root)
+        .with_context(|| format!("read_dir {}", dataset_root.display()))?
+    {
+        let prefix_dir = prefix_dir?;
+        let prefix_path = prefix_dir.path();
+        if !prefix_path.is_dir() {
+            continue;
+        }
+        let prefix_name = prefix_path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+        if !prefix_name.starts_with("0x") {
+            continue;
+        }
+        for pkg_dir in fs::read_dir(&prefix_path)? {
+            let pkg_dir = pkg_dir?;
+            let pkg_path = pkg_dir.path();
+            let pkg_name = pkg_path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default()
+                .to_string();
+            if pkg_name.is_empty() {
+                continue;
+            }
+            entries.push((prefix_name.clone(), pkg_name));
+        }
+    }
+
+    entries.sort();
+
+    let mut out = Vec::new();
+    for (prefix, suffix62) in entries.into_iter().take(limit) {
+        let prefix_hex = prefix.strip_prefix("0x").unwrap_or(prefix.as_str());
+        out.push(format!("0x{prefix_hex}{suffix62}"));
+    }
+    Ok(out)
+}
@@ -0,0 +1,751 @@
+//! Comparison of interface inventories.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::dataset::normalize_package_id;
+use crate::inventory::{
+    package_inventory_from_normalized_modules_file, sort_struct_fields, type_sig_list_display,
+    FunctionInv, InventoryOptions, ModuleInventory, PackageInventory, StructInv, TypeSig,
+};
+
+/// A single function/struct level difference found while verifying a module.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffEntry {
+    pub module: String,
+    pub category: String,
+    pub name: String,
+    /// Extra context for some categories, e.g. both argument lists of a
+    /// `type_argument_mismatch`.
+    pub detail: Option<String>,
+    pub severity: Severity,
+}
+
+/// Whether a difference can break code written against `self` (a) once it runs against
+/// `other` (b).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Breaking,
+    NonBreaking,
+}
+
+impl Severity {
+    /// Items only in `other` are additions, and a source digest says nothing about the
+    /// interface; every other category removes or changes something `self` exposes.
+    pub fn of_category(category: &str) -> Severity {
+        match category {
+            "function_missing_self"
+            | "struct_missing_self"
+            | "enum_missing_self"
+            | "source_digest_mismatch" => Severity::NonBreaking,
+            _ => Severity::Breaking,
+        }
+    }
+
+    /// A removed or changed function only breaks callers if `self` exposed it as `public`;
+    /// private and friend functions (entry or not) may change freely, which also makes
+    /// widening their visibility non-breaking. Unknown visibility counts as public.
+    pub fn of_function_change(baseline: &FunctionInv) -> Severity {
+        match baseline.visibility.as_deref() {
+            Some("private") | Some("friend") => Severity::NonBreaking,
+            _ => Severity::Breaking,
+        }
+    }
+}
+
+/// Whether both inventories carry a module address and those addresses differ.
+/// Addresses are compared in normalized form since RPC may use short hex.
+pub fn module_addresses_differ(a: &ModuleInventory, b: &ModuleInventory) -> bool {
+    match (&a.module_address, &b.module_address) {
+        (Some(x), Some(y)) => normalize_package_id(x) != normalize_package_id(y),
+        _ => false,
+    }
+}
+
+/// What differs between two versions of one struct (`self` = a, `other` = b).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StructDiff {
+    /// Fields on both sides whose type differs.
+    pub fields_changed: Vec<String>,
+    pub fields_only_self: Vec<String>,
+    pub fields_only_other: Vec<String>,
+    /// Same fields, declared in a different order.
+    pub field_order_changed: bool,
+    pub abilities_changed: bool,
+    /// Type parameter count, phantom markers or ability constraints differ.
+    pub type_params_changed: bool,
+}
+
+fn struct_diff(a: &StructInv, b: &StructInv) -> StructDiff {
+    let a_fields: BTreeMap<&String, &TypeSig> = a.fields.iter().map(|(n, t)| (n, t)).collect();
+    let b_fields: BTreeMap<&String, &TypeSig> = b.fields.iter().map(|(n, t)| (n, t)).collect();
+    let mut out = StructDiff {
+        abilities_changed: a.abilities != b.abilities,
+        type_params_changed: a.type_params != b.type_params
+            || a.phantom_type_params != b.phantom_type_params
+            || a.type_param_abilities != b.type_param_abilities,
+        ..Default::default()
+    };
+    for (name, ta) in &a_fields {
+        match b_fields.get(name) {
+            None => out.fields_only_self.push((*name).clone()),
+            Some(tb) if ta != tb => out.fields_changed.push((*name).clone()),
+            Some(_) => {}
+        }
+    }
+    for name in b_fields.keys() {
+        if !a_fields.contains_key(name) {
+            out.fields_only_other.push((*name).clone());
+        }
+    }
+    out.field_order_changed = out.fields_changed.is_empty()
+        && out.fields_only_self.is_empty()
+        && out.fields_only_other.is_empty()
+        && a.fields != b.fields;
+    out
+}
+
+/// Per-module result of `diff_module_inventory`.
+pub struct ModuleInventoryDiff {
+    pub summary: BTreeMap<String, usize>,
+    /// `(category, item name, detail, severity)` for every recorded difference.
+    pub entries: Vec<(String, String, Option<String>, Severity)>,
+    /// Keyed by struct name.
+    pub struct_diffs: BTreeMap<String, StructDiff>,
+}
+
+/// Human-readable lines describing how module `a` (local) differs from `b` (rpc).
+pub fn describe_module_inventory_diff(a: &ModuleInventory, b: &ModuleInventory) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (name, fa) in &a.functions {
+        let Some(fb) = b.functions.get(name) else {
+            lines.push(format!("function {}: missing on rpc", name));
+            continue;
+        };
+        if fa.visibility != fb.visibility || fa.is_entry != fb.is_entry {
+            lines.push(format!(
+                "function {}: visibility differs (local {:?} entry={:?} vs rpc {:?} entry={:?})",
+                name, fa.visibility, fa.is_entry, fb.visibility, fb.is_entry
+            ));
+        }
+        if fa.type_params != fb.type_params {
+            lines.push(format!(
+                "function {}: type params differ (local {:?} vs rpc {:?})",
+                name, fa.type_params, fb.type_params
+            ));
+        }
+        if fa.params != fb.params {
+            lines.push(format!(
+                "function {}: params differ (local {} vs rpc {})",
+                name,
+                type_sig_list_display(&fa.params),
+                type_sig_list_display(&fb.params)
+            ));
+        }
+        if fa.returns != fb.returns {
+            lines.push(format!(
+                "function {}: returns differ (local {} vs rpc {})",
+                name,
+                type_sig_list_display(&fa.returns),
+                type_sig_list_display(&fb.returns)
+            ));
+        }
+    }
+    for name in b.functions.keys().filter(|n| !a.functions.contains_key(*n)) {
+        lines.push(format!("function {}: missing locally", name));
+    }
+
+    for (name, sa) in &a.structs {
+        let Some(sb) = b.structs.get(name) else {
+            lines.push(format!("struct {}: missing on rpc", name));
+            continue;
+        };
+        if sa.abilities != sb.abilities {
+            lines.push(format!(
+                "struct {}: abilities differ (local {:?} vs rpc {:?})",
+                name, sa.abilities, sb.abilities
+            ));
+        }
+        if sa.type_params != sb.type_params
+            || sa.phantom_type_params != sb.phantom_type_params
+            || sa.type_param_abilities != sb.type_param_abilities
+        {
+            lines.push(format!(
+                "struct {}: type params differ (local phantom {:?} abilities {:?} vs rpc phantom {:?} abilities {:?})",
+                name,
+                sa.phantom_type_params,
+                sa.type_param_abilities,
+                sb.phantom_type_params,
+                sb.type_param_abilities
+            ));
+        }
+        if sa.fields != sb.fields {
+            let render = |fields: &[(String, TypeSig)]| {
+                let parts: Vec<String> = fields
+                    .iter()
+                    .map(|(f, t)| format!("{}: {}", f, t.to_move_string()))
+                    .collect();
+                format!("{{ {} }}", parts.join(", "))
+            };
+            lines.push(format!(
+                "struct {}: fields differ (local {} vs rpc {})",
+                name,
+                render(&sa.fields),
+                render(&sb.fields)
+            ));
+        }
+    }
+    for name in b.structs.keys().filter(|n| !a.structs.contains_key(*n)) {
+        lines.push(format!("struct {}: missing locally", name));
+    }
+
+    for (name, ea) in &a.enums {
+        match b.enums.get(name) {
+            None => lines.push(format!("enum {}: missing on rpc", name)),
+            Some(eb) if ea != eb => lines.push(format!("enum {}: definition differs", name)),
+            Some(_) => {}
+        }
+    }
+    for name in b.enums.keys().filter(|n| !a.enums.contains_key(*n)) {
+        lines.push(format!("enum {}: missing locally", name));
+    }
+    lines
+}
+
+/// When `a` and `b` reference the same datatype (possibly behind `&`, `&mut` or `vector`)
+/// but with different type arguments, return both argument lists.
+fn type_argument_difference(a: &TypeSig, b: &TypeSig) -> Option<(Value, Value)> {
+    fn walk(a: &Value, b: &Value) -> Option<(Value, Value)> {
+        for wrapper in ["Reference", "MutableReference", "Vector"] {
+            if let (Some(ia), Some(ib)) = (a.get(wrapper), b.get(wrapper)) {
+                return walk(ia, ib);
+            }
+        }
+        let (sa, sb) = (a.get("Struct")?, b.get("Struct")?);
+        let same_datatype = ["address", "module", "name"]
+            .iter()
+            .all(|k| sa.get(k) == sb.get(k));
+        let (ta, tb) = (sa.get("typeArguments")?, sb.get("typeArguments")?);
+        (same_datatype && ta != tb).then(|| (ta.clone(), tb.clone()))
+    }
+    let a: Value = serde_json::from_str(&a.0).ok()?;
+    let b: Value = serde_json::from_str(&b.0).ok()?;
+    walk(&a, &b)
+}
+
+/// If every differing parameter/return of two same-shaped functions is a
+/// `type_argument_difference`, describe each one; otherwise `None`.
+fn type_argument_only_diffs(a: &FunctionInv, b: &FunctionInv) -> Option<Vec<String>> {
+    if a.params.len() != b.params.len() || a.returns.len() != b.returns.len() {
+        return None;
+    }
+    let mut details = Vec::new();
+    let positions = a
+        .params
+        .iter()
+        .zip(&b.params)
+        .enumerate()
+        .map(|(i, p)| ("param", i, p))
+        .chain(
+            a.returns
+                .iter()
+                .zip(&b.returns)
+                .enumerate()
+                .map(|(i, p)| ("return", i, p)),
+        );
+    for (kind, i, (ta, tb)) in positions {
+        if ta == tb {
+            continue;
+        }
+        let (args_a, args_b) = type_argument_difference(ta, tb)?;
+        details.push(format!("{} {}: {} vs {}", kind, i, args_a, args_b));
+    }
+    (!details.is_empty()).then_some(details)
+}
+
+/// Diff two module inventories into per-category counts, the entries behind those
+/// counts, and per-struct details.
+fn diff_module_inventory(a: &ModuleInventory, b: &ModuleInventory) -> ModuleInventoryDiff {
+    let mut diffs: BTreeMap<String, usize> = BTreeMap::new();
+    let mut entries: Vec<(String, String, Option<String>, Severity)> = Vec::new();
+    let mut struct_diffs: BTreeMap<String, StructDiff> = BTreeMap::new();
+    let mut record_with = |category: &str, name: &str, detail: Option<String>, severity| {
+        *diffs.entry(category.to_string()).or_default() += 1;
+        entries.push((category.to_string(), name.to_string(), detail, severity));
+    };
+
+    for (k, va) in &a.functions {
+        let severity = Severity::of_function_change(va);
+        let mut record = |category: &str, name: &str, detail: Option<String>| {
+            record_with(category, name, detail, severity)
+        };
+        match b.functions.get(k) {
+            None => record("function_missing_other", k, None),
+            Some(vb) => {
+                // Same arity but different constraints gets its own bucket; an arity change
+                // is an ordinary signature mismatch.
+                let same_arity = va.type_params.len() == vb.type_params.len();
+                if same_arity && va.type_params != vb.type_params {
+                    record("function_type_param_abilities_mismatch", k, None);
+                }
+                if !same_arity || va.visibility != vb.visibility || va.is_entry != vb.is_entry {
+                    record("function_mismatch", k, None);
+                } else if va.params != vb.params || va.returns != vb.returns {
+                    match type_argument_only_diffs(va, vb) {
+                        Some(details) => {
+                            record("type_argument_mismatch", k, Some(details.join("; ")))
+                        }
+                        None => record("function_mismatch", k, None),
+                    }
+                }
+            }
+        }
+    }
+    let mut record = |category: &str, name: &str, detail: Option<String>| {
+        record_with(category, name, detail, Severity::of_category(category))
+    };
+    for k in b.functions.keys() {
+        if !a.functions.contains_key(k) {
+            record("function_missing_self", k, None);
+        }
+    }
+
+    for (k, va) in &a.structs {
+        match b.structs.get(k) {
+            None => record("struct_missing_other", k, None),
+            Some(vb) => {
+                // Phantom and constraint changes get their own category since they are
+                // source-breaking even when everything else matches.
+                if va.phantom_type_params != vb.phantom_type_params
+                    || va.type_param_abilities != vb.type_param_abilities
+                {
+                    record("struct_type_param_mismatch", k, None);
+                }
+                // Struct layout is positional, so the same fields in a different order is
+                // breaking too, but worth telling apart from an actual field change.
+                let same_field_set = va.fields != vb.fields && {
+                    let mut fa = va.fields.clone();
+                    let mut fb = vb.fields.clone();
+                    fa.sort();
+                    fb.sort();
+                    fa == fb
+                };
+                if same_field_set {
+                    record("struct_field_order_mismatch", k, None);
+                }
+                if va.abilities != vb.abilities
+                    || va.type_params != vb.type_params
+                    || (va.fields != vb.fields && !same_field_set)
+                {
+                    record("struct_mismatch", k, None);
+                }
+                if va != vb {
+                    struct_diffs.insert(k.clone(), struct_diff(va, vb));
+                }
+            }
+        }
+    }
+    for k in b.structs.keys() {
+        if !a.structs.contains_key(k) {
+            record("struct_missing_self", k, None);
+        }
+    }
+
+    for (k, va) in &a.enums {
+        match b.enums.get(k) {
+            None => record("enum_missing_other", k, None),
+            Some(vb) => {
+                if va != vb {
+                    record("enum_mismatch", k, None);
+                }
+            }
+        }
+    }
+    for k in b.enums.keys() {
+        if !a.enums.contains_key(k) {
+            record("enum_missing_self", k, None);
+        }
+    }
+
+    ModuleInventoryDiff {
+        summary: diffs,
+        entries,
+        struct_diffs,
+    }
+}
+
+/// Module-by-module diff of two package inventories (`self` = a, `other` = b).
+#[derive(Debug, Default, Serialize)]
+pub struct PackageInventoryDiff {
+    /// Modules present in b but not in a.
+    pub modules_missing_self: Vec<String>,
+    /// Modules present in a but not in b.
+    pub modules_missing_other: Vec<String>,
+    pub modules_with_diffs: Vec<String>,
+    pub diff_summary: BTreeMap<String, usize>,
+    pub diffs: Vec<DiffEntry>,
+    /// Keyed by `module::Struct`.
+    pub struct_diffs: BTreeMap<String, StructDiff>,
+}
+
+pub fn diff_package_inventory(
+    a: &PackageInventory,
+    b: &PackageInventory,
+    check_module_address: bool,
+) -> PackageInventoryDiff {
+    let mut out = PackageInventoryDiff::default();
+
+    for m in b.modules.keys() {
+        if !a.modules.contains_key(m) {
+            out.modules_missing_self.push(m.clone());
+        }
+    }
+    for m in a.modules.keys() {
+        if !b.modules.contains_key(m) {
+            out.modules_missing_other.push(m.clone());
+        }
+    }
+
+    for (mname, b_m) in &b.modules {
+        let Some(a_m) = a.modules.get(mname) else {
+            continue;
+        };
+        let mut module_diff = diff_module_inventory(a_m, b_m);
+        if check_module_address && module_addresses_differ(a_m, b_m) {
+            *module_diff
+                .summary
+                .entry("module_address_mismatch".to_string())
+                .or_default() += 1;
+            module_diff.entries.push((
+                "module_address_mismatch".to_string(),
+                mname.clone(),
+                None,
+                Severity::Breaking,
+            ));
+        }
+        if !module_diff.summary.is_empty() {
+            out.modules_with_diffs.push(mname.clone());
+            for (k, v) in module_diff.summary {
+                *out.diff_summary.entry(k).or_default() += v;
+            }
+            for (sname, d) in module_diff.struct_diffs {
+                out.struct_diffs.insert(format!("{}::{}", mname, sname), d);
+            }
+            for (category, name, detail, severity) in module_diff.entries {
+                out.diffs.push(DiffEntry {
+                    module: mname.clone(),
+                    severity,
+                    category,
+                    name,
+                    detail,
+                });
+            }
+        }
+    }
+
+    out
+}
+
+impl PackageInventoryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.modules_missing_self.is_empty()
+            && self.modules_missing_other.is_empty()
+            && self.modules_with_diffs.is_empty()
+    }
+
+    /// Whether `other` dropped a module of `self` or any diff entry is breaking.
+    pub fn is_breaking(&self) -> bool {
+        !self.modules_missing_other.is_empty()
+            || self.diffs.iter().any(|d| d.severity == Severity::Breaking)
+    }
+
+    /// Severity of each `diff_summary` category: breaking if any of its entries is.
+    pub fn category_severity(&self) -> BTreeMap<String, Severity> {
+        let mut out: BTreeMap<String, Severity> = self
+            .diff_summary
+            .keys()
+            .map(|c| (c.clone(), Severity::NonBreaking))
+            .collect();
+        for d in &self.diffs {
+            if d.severity == Severity::Breaking {
+                out.insert(d.category.clone(), Severity::Breaking);
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct NormalizedCompareReport {
+    pub left: String,
+    pub right: String,
+    pub ok: bool,
+    #[serde(flatten)]
+    pub diff: PackageInventoryDiff,
+}
+
+/// Diff two normalized-modules JSON files without touching bytecode or RPC.
+pub fn compare_normalized_files(
+    left: &Path,
+    right: &Path,
+    opts: &InventoryOptions,
+    sort_fields: bool,
+    check_module_address: bool,
+) -> Result<NormalizedCompareReport> {
+    let mut a = package_inventory_from_normalized_modules_file(left, opts)?;
+    let mut b = package_inventory_from_normalized_modules_file(right, opts)?;
+    if sort_fields {
+        sort_struct_fields(&mut a);
+        sort_struct_fields(&mut b);
+    }
+    let diff = diff_package_inventory(&a, &b, check_module_address);
+    Ok(NormalizedCompareReport {
+        left: left.display().to_string(),
+        right: right.display().to_string(),
+        ok: diff.is_empty(),
+        diff,
+    })
+}
+
+/// Added/removed/changed item names between two package versions.
+#[derive(Debug, Default, Serialize)]
+pub struct ItemChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl ItemChanges {
+    /// Record the differences between two name-keyed maps, prefixing each name.
+    pub fn record<T: PartialEq>(
+        &mut self,
+        prefix: &str,
+        old: &BTreeMap<String, T>,
+        new: &BTreeMap<String, T>,
+    ) {
+        for (name, v) in old {
+            match new.get(name) {
+                None => self.removed.push(format!("{}{}", prefix, name)),
+                Some(nv) if nv != v => self.changed.push(format!("{}{}", prefix, name)),
+                Some(_) => {}
+            }
+        }
+        for name in new.keys().filter(|k| !old.contains_key(*k)) {
+            self.added.push(format!("{}{}", prefix, name));
+        }
+    }
+}
+
+/// Interface changes from `old_package_id` to `new_package_id`. Functions and structs are
+/// named `module::item`; items of added/removed modules are listed as added/removed.
+#[derive(Debug, Serialize)]
+pub struct PackageDiff {
+    pub old_package_id: String,
+    pub new_package_id: String,
+    pub modules: ItemChanges,
+    pub functions: ItemChanges,
+    pub structs: ItemChanges,
+}
+
+pub fn package_diff(
+    old_package_id: &str,
+    old: &PackageInventory,
+    new_package_id: &str,
+    new: &PackageInventory,
+) -> PackageDiff {
+    let mut modules = ItemChanges::default();
+    modules.record("", &old.modules, &new.modules);
+
+    let empty = ModuleInventory {
+        module_address: None,
+        functions: BTreeMap::new(),
+        structs: BTreeMap::new(),
+        enums: BTreeMap::new(),
+    };
+    let mut functions = ItemChanges::default();
+    let mut structs = ItemChanges::default();
+    let names: BTreeSet<&String> = old.modules.keys().chain(new.modules.keys()).collect();
+    for name in names {
+        let old_m = old.modules.get(name).unwrap_or(&empty);
+        let new_m = new.modules.get(name).unwrap_or(&empty);
+        let prefix = format!("{}::", name);
+        functions.record(&prefix, &old_m.functions, &new_m.functions);
+        structs.record(&prefix, &old_m.structs, &new_m.structs);
+    }
+
+    PackageDiff {
+        old_package_id: old_package_id.to_string(),
+        new_package_id: new_package_id.to_string(),
+        modules,
+        functions,
+        structs,
+    }
+}
+
+/// A public item whose interface differs from the baseline.
+#[derive(Debug, Serialize)]
+pub struct SignatureChange {
+    /// `function module::name` or `struct module::name`.
+    pub item: String,
+    pub baseline: String,
+    pub current: String,
+}
+
+/// Public functions and structs of a baseline inventory that are gone (or no longer
+/// public) or have a different signature in the current inventory.
+#[derive(Debug, Serialize)]
+pub struct RegressionReport {
+    pub package_id: String,
+    pub baseline: String,
+    pub ok: bool,
+    pub removed: Vec<String>,
+    pub changed: Vec<SignatureChange>,
+}
+
+pub fn interface_regressions(
+    package_id: &str,
+    baseline_path: &str,
+    baseline: &PackageInventory,
+    current: &PackageInventory,
+) -> RegressionReport {
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for (mname, bm) in &baseline.modules {
+        let cm = current.modules.get(mname);
+        for (name, bf) in &bm.functions {
+            if bf.visibility.as_deref() != Some("public") {
+                continue;
+            }
+            let item = format!("function {}::{}", mname, name);
+            match cm.and_then(|m| m.functions.get(name)) {
+                Some(cf) if cf.visibility.as_deref() == Some("public") => {
+                    let (was, now) = (bf.signature(), cf.signature());
+                    if was != now {
+                        changed.push(SignatureChange {
+                            item,
+                            baseline: was,
+                            current: now,
+                        });
+                    }
+                }
+                _ => removed.push(item),
+            }
+        }
+        for (name, bs) in &bm.structs {
+            let item = format!("struct {}::{}", mname, name);
+            match cm.and_then(|m| m.structs.get(name)) {
+                Some(cs) if cs != bs => changed.push(SignatureChange {
+                    item,
+                    baseline: serde_json::to_string(bs).expect("serialize"),
+                    current: serde_json::to_string(cs).expect("serialize"),
+                }),
+                Some(_) => {}
+                None => removed.push(item),
+            }
+        }
+    }
+    RegressionReport {
+        package_id: package_id.to_string(),
+        baseline: baseline_path.to_string(),
+        ok: removed.is_empty() && changed.is_empty(),
+        removed,
+        changed,
+    }
+}
+
+/// Classify one item from the current snapshot, the old snapshot and the chain (`None` =
+/// absent). `None` when all three agree.
+pub fn classify_three_way(
+    current: Option<&String>,
+    old: Option<&String>,
+    rpc: Option<&String>,
+) -> Option<&'static str> {
+    match (current == rpc, old == rpc, current == old) {
+        (true, true, _) => None,
+        // Chain still matches the old snapshot; the current local data diverged.
+        (false, true, _) => Some("local-only"),
+        // Current local data and chain agree; only the old snapshot is behind.
+        (true, false, _) => Some("both-agree-differ-from-old"),
+        // Both snapshots agree but the chain moved.
+        (false, false, true) => Some("chain-change"),
+        (false, false, false) => Some("all-differ"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(visibility: &str, params: &[&str]) -> FunctionInv {
+        FunctionInv {
+            visibility: Some(visibility.to_string()),
+            is_entry: Some(false),
+            type_params: Vec::new(),
+            params: params.iter().map(|p| TypeSig(p.to_string())).collect(),
+            returns: Vec::new(),
+            takes_tx_context: false,
+        }
+    }
+
+    fn package(functions: &[(&str, FunctionInv)]) -> PackageInventory {
+        let module = ModuleInventory {
+            module_address: None,
+            functions: functions
+                .iter()
+                .map(|(name, f)| (name.to_string(), f.clone()))
+                .collect(),
+            structs: BTreeMap::new(),
+            enums: BTreeMap::new(),
+        };
+        PackageInventory {
+            modules: BTreeMap::from([("m".to_string(), module)]),
+            return_keys: BTreeMap::new(),
+        }
+    }
+
+    fn severity_of(diff: &PackageInventoryDiff, name: &str) -> Severity {
+        diff.diffs
+            .iter()
+            .find(|d| d.name == name)
+            .unwrap_or_else(|| panic!("no diff for {name}"))
+            .severity
+    }
+
+    #[test]
+    fn function_severity_follows_baseline_visibility() {
+        let baseline = package(&[
+            ("pub_removed", function("public", &[])),
+            ("pub_changed", function("public", &["\"U64\""])),
+            ("friend_removed", function("friend", &[])),
+            ("private_changed", function("private", &["\"U64\""])),
+            ("widened", function("private", &[])),
+        ]);
+        let current = package(&[
+            ("pub_changed", function("public", &["\"U8\""])),
+            ("private_changed", function("private", &["\"U8\""])),
+            ("widened", function("public", &[])),
+            ("added", function("public", &[])),
+        ]);
+        let diff = diff_package_inventory(&baseline, &current, false);
+
+        assert_eq!(severity_of(&diff, "pub_removed"), Severity::Breaking);
+        assert_eq!(severity_of(&diff, "pub_changed"), Severity::Breaking);
+        assert_eq!(severity_of(&diff, "friend_removed"), Severity::NonBreaking);
+        assert_eq!(severity_of(&diff, "private_changed"), Severity::NonBreaking);
+        assert_eq!(severity_of(&diff, "widened"), Severity::NonBreaking);
+        assert_eq!(severity_of(&diff, "added"), Severity::NonBreaking);
+        assert!(diff.is_breaking());
+        assert_eq!(
+            diff.category_severity()["function_missing_other"],
+            Severity::Breaking
+        );
+
+        let private_only = package(&[("private_changed", function("private", &["\"U64\""]))]);
+        let diff = diff_package_inventory(&private_only, &current, false);
+        assert!(!diff.is_breaking());
+    }
+}
@@ -0,0 +1,557 @@
+//! Package reads against the Sui GraphQL service, shaped like their JSON-RPC counterparts.
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use futures::future::BoxFuture;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use move_binary_format::CompiledModule;
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::rpc::{rpc_call, PackageRpc, RawPackage};
+
+/// Largest page the public GraphQL service serves for a connection.
+const PAGE_SIZE: usize = 50;
+
+const PACKAGE_HEADER_QUERY: &str = "query ($address: SuiAddress!) {
+  package(address: $address) {
+    digest
+    version
+    linkage { originalId }
+  }
+}";
+
+const PACKAGE_DIGEST_QUERY: &str = "query ($address: SuiAddress!) {
+  object(address: $address) { digest }
+}";
+
+const MODULE_BYTES_QUERY: &str = "query ($address: SuiAddress!, $first: Int!, $after: String) {
+  package(address: $address) {
+    modules(first: $first, after: $after) {
+      pageInfo { hasNextPage endCursor }
+      nodes { name bytes }
+    }
+  }
+}";
+
+const MODULE_FRIENDS_QUERY: &str =
+    "query ($address: SuiAddress!, $module: String!, $first: Int!, $after: String) {
+  package(address: $address) {
+    module(name: $module) {
+      friends(first: $first, after: $after) {
+        pageInfo { hasNextPage endCursor }
+        nodes { name package { address } }
+      }
+    }
+  }
+}";
+
+const MODULE_STRUCTS_QUERY: &str =
+    "query ($address: SuiAddress!, $module: String!, $first: Int!, $after: String) {
+  package(address: $address) {
+    module(name: $module) {
+      structs(first: $first, after: $after) {
+        pageInfo { hasNextPage endCursor }
+        nodes {
+          name
+          abilities
+          typeParameters { constraints isPhantom }
+          fields { name type { signature } }
+        }
+      }
+    }
+  }
+}";
+
+const MODULE_ENUMS_QUERY: &str =
+    "query ($address: SuiAddress!, $module: String!, $first: Int!, $after: String) {
+  package(address: $address) {
+    module(name: $module) {
+      enums(first: $first, after: $after) {
+        pageInfo { hasNextPage endCursor }
+        nodes {
+          name
+          abilities
+          typeParameters { constraints isPhantom }
+          variants { name fields { name type { signature } } }
+        }
+      }
+    }
+  }
+}";
+
+const MODULE_FUNCTIONS_QUERY: &str =
+    "query ($address: SuiAddress!, $module: String!, $first: Int!, $after: String) {
+  package(address: $address) {
+    module(name: $module) {
+      functions(first: $first, after: $after) {
+        pageInfo { hasNextPage endCursor }
+        nodes {
+          name
+          visibility
+          isEntry
+          typeParameters { constraints }
+          parameters { signature }
+          return { signature }
+        }
+      }
+    }
+  }
+}";
+
+/// Client for a Sui GraphQL endpoint (`--rpc-kind graphql`).
+pub struct GraphQlClient {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl GraphQlClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.to_string(),
+        }
+    }
+
+    /// Run one query under the RPC retry and timeout policy and return its `data`.
+    async fn query(&self, label: &'static str, query: &str, variables: Value) -> Result<Value> {
+        let body = json!({ "query": query, "variables": variables });
+        let resp: Value = rpc_call(label, || {
+            let request = self.http.post(&self.url).json(&body);
+            async move {
+                request
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<Value>()
+                    .await
+            }
+        })
+        .await
+        .with_context(|| format!("graphql {}", label))?;
+        if let Some(errors) = resp
+            .get("errors")
+            .and_then(Value::as_array)
+            .filter(|e| !e.is_empty())
+        {
+            let messages: Vec<&str> = errors
+                .iter()
+                .filter_map(|e| e.get("message").and_then(Value::as_str))
+                .collect();
+            return Err(anyhow!("graphql {}: {}", label, messages.join("; ")));
+        }
+        Ok(resp.get("data").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Collect every node of the connection at `path` (below `data`), following cursors.
+    /// A `null` anywhere along the path means the package or module does not exist.
+    async fn paginate(
+        &self,
+        label: &'static str,
+        query: &str,
+        mut variables: Value,
+        path: &[&str],
+    ) -> Result<Vec<Value>> {
+        let mut nodes = Vec::new();
+        variables["first"] = json!(PAGE_SIZE);
+        loop {
+            let data = self.query(label, query, variables.clone()).await?;
+            let connection = path
+                .iter()
+                .try_fold(&data, |v, key| v.get(key).filter(|v| !v.is_null()))
+                .ok_or_else(|| anyhow!("graphql {}: no {} in response", label, path.join(".")))?;
+            if let Some(page) = connection.get("nodes").and_then(Value::as_array) {
+                nodes.extend(page.iter().cloned());
+            }
+            let page_info = connection.get("pageInfo");
+            let has_next = page_info
+                .and_then(|p| p.get("hasNextPage"))
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            match page_info.and_then(|p| p.get("endCursor")) {
+                Some(cursor) if has_next && !cursor.is_null() => {
+                    variables["after"] = cursor.clone();
+                }
+                _ => return Ok(nodes),
+            }
+        }
+    }
+
+    async fn module_bytes(&self, package_id: ObjectID) -> Result<BTreeMap<String, Vec<u8>>> {
+        let nodes = self
+            .paginate(
+                "graphql(package_modules)",
+                MODULE_BYTES_QUERY,
+                json!({ "address": package_id.to_string() }),
+                &["package", "modules"],
+            )
+            .await?;
+        let mut module_map = BTreeMap::new();
+        for node in nodes {
+            let name = node
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("module without a name in {}", package_id))?;
+            let bytes = node
+                .get("bytes")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("module {}::{} has no bytes", package_id, name))?;
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(bytes)
+                .with_context(|| format!("decode module {}::{}", package_id, name))?;
+            module_map.insert(name.to_string(), bytes);
+        }
+        Ok(module_map)
+    }
+
+    async fn module_connection(
+        &self,
+        label: &'static str,
+        query: &str,
+        package_id: ObjectID,
+        module: &str,
+        connection: &str,
+    ) -> Result<Vec<Value>> {
+        self.paginate(
+            label,
+            query,
+            json!({ "address": package_id.to_string(), "module": module }),
+            &["package", "module", connection],
+        )
+        .await
+        .with_context(|| format!("{} of {}::{}", connection, package_id, module))
+    }
+
+    /// One module in the `getNormalizedMoveModulesByPackage` shape. The address and file
+    /// format version come from the module bytes, so upgraded packages report their
+    /// original id just as JSON-RPC does.
+    async fn normalized_module(
+        &self,
+        package_id: ObjectID,
+        name: &str,
+        bytes: &[u8],
+    ) -> Result<Value> {
+        let compiled = CompiledModule::deserialize_with_defaults(bytes)
+            .with_context(|| format!("deserialize rpc module {}::{}", package_id, name))?;
+
+        let friends: Vec<Value> = self
+            .module_connection(
+                "graphql(module_friends)",
+                MODULE_FRIENDS_QUERY,
+                package_id,
+                name,
+                "friends",
+            )
+            .await?
+            .iter()
+            .map(|f| {
+                json!({
+                    "address": short_address(
+                        f.pointer("/package/address").and_then(Value::as_str).unwrap_or("")
+                    ),
+                    "name": f.get("name").cloned().unwrap_or(Value::Null),
+                })
+            })
+            .collect();
+
+        let mut structs = Map::new();
+        for s in self
+            .module_connection(
+                "graphql(module_structs)",
+                MODULE_STRUCTS_QUERY,
+                package_id,
+                name,
+                "structs",
+            )
+            .await?
+        {
+            structs.insert(
+                node_name(&s)?,
+                json!({
+                    "abilities": ability_set(s.get("abilities")),
+                    "typeParameters": datatype_type_params(&s),
+                    "fields": normalized_fields(s.get("fields")),
+                }),
+            );
+        }
+
+        let mut enums = Map::new();
+        for e in self
+            .module_connection(
+                "graphql(module_enums)",
+                MODULE_ENUMS_QUERY,
+                package_id,
+                name,
+                "enums",
+            )
+            .await?
+        {
+            let mut variants = Map::new();
+            let mut order = Vec::new();
+            for v in e
+                .get("variants")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let vname = node_name(v)?;
+                order.push(Value::String(vname.clone()));
+                variants.insert(vname, Value::Array(normalized_fields(v.get("fields"))));
+            }
+            enums.insert(
+                node_name(&e)?,
+                json!({
+                    "abilities": ability_set(e.get("abilities")),
+                    "typeParameters": datatype_type_params(&e),
+                    "variants": variants,
+                    "variantDeclarationOrder": order,
+                }),
+            );
+        }
+
+        let mut functions = Map::new();
+        for f in self
+            .module_connection(
+                "graphql(module_functions)",
+                MODULE_FUNCTIONS_QUERY,
+                package_id,
+                name,
+                "functions",
+            )
+            .await?
+        {
+            let visibility = pascal_case(f.get("visibility").and_then(Value::as_str).unwrap_or(""));
+            let is_entry = f.get("isEntry").and_then(Value::as_bool).unwrap_or(false);
+            // JSON-RPC only exposes public, friend and entry functions.
+            if visibility == "Private" && !is_entry {
+                continue;
+            }
+            let type_params: Vec<Value> = f
+                .get("typeParameters")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .map(|tp| ability_set(tp.get("constraints")))
+                .collect();
+            functions.insert(
+                node_name(&f)?,
+                json!({
+                    "visibility": visibility,
+                    "isEntry": is_entry,
+                    "typeParameters": type_params,
+                    "parameters": signatures(f.get("parameters")),
+                    "return": signatures(f.get("return")),
+                }),
+            );
+        }
+
+        Ok(json!({
+            "fileFormatVersion": compiled.version,
+            "address": compiled.self_id().address().to_hex_literal(),
+            "name": name,
+            "friends": friends,
+            "structs": structs,
+            "enums": enums,
+            "exposedFunctions": functions,
+        }))
+    }
+}
+
+impl PackageRpc for GraphQlClient {
+    fn package_digest(&self, package_id: ObjectID) -> BoxFuture<'_, Result<Option<String>>> {
+        Box::pin(async move {
+            let data = self
+                .query(
+                    "graphql(object_digest)",
+                    PACKAGE_DIGEST_QUERY,
+                    json!({ "address": package_id.to_string() }),
+                )
+                .await?;
+            Ok(data
+                .pointer("/object/digest")
+                .and_then(Value::as_str)
+                .map(str::to_string))
+        })
+    }
+
+    fn package_contents(
+        &self,
+        package_id: ObjectID,
+        _method: &'static str,
+    ) -> BoxFuture<'_, Result<(String, RawPackage)>> {
+        Box::pin(async move {
+            let header = self
+                .query(
+                    "graphql(package)",
+                    PACKAGE_HEADER_QUERY,
+                    json!({ "address": package_id.to_string() }),
+                )
+                .await
+                .with_context(|| format!("fetch package object {}", package_id))?;
+            let package = header
+                .get("package")
+                .filter(|p| !p.is_null())
+                .ok_or_else(|| anyhow!("object {} is not a package", package_id))?;
+            let digest = package
+                .get("digest")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("missing digest for {}", package_id))?
+                .to_string();
+            let linkage = package
+                .get("linkage")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|l| l.get("originalId").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect();
+            let version = package.get("version").and_then(Value::as_u64);
+            let module_map = self.module_bytes(package_id).await?;
+            Ok((
+                digest,
+                RawPackage {
+                    module_map,
+                    linkage,
+                    version,
+                },
+            ))
+        })
+    }
+
+    fn normalized_modules(&self, package_id: ObjectID) -> BoxFuture<'_, Result<Value>> {
+        Box::pin(async move {
+            let mut modules = Map::new();
+            for (name, bytes) in self.module_bytes(package_id).await? {
+                let module = self.normalized_module(package_id, &name, &bytes).await?;
+                modules.insert(name, module);
+            }
+            Ok(Value::Object(modules))
+        })
+    }
+}
+
+fn node_name(node: &Value) -> Result<String> {
+    node.get("name")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("graphql node without a name: {}", node))
+}
+
+/// `PUBLIC` / `u64` -> `Public` / `U64`.
+fn pascal_case(s: &str) -> String {
+    let lower = s.to_ascii_lowercase();
+    let mut chars = lower.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Full-width GraphQL addresses in the short `0x2` form JSON-RPC uses.
+fn short_address(addr: &str) -> String {
+    let hex = addr
+        .strip_prefix("0x")
+        .unwrap_or(addr)
+        .trim_start_matches('0');
+    format!("0x{}", if hex.is_empty() { "0" } else { hex })
+}
+
+/// GraphQL ability list (`["COPY", "DROP"]`) as a JSON-RPC ability set.
+fn ability_set(abilities: Option<&Value>) -> Value {
+    let names: Vec<String> = abilities
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(pascal_case)
+        .collect();
+    json!({ "abilities": names })
+}
+
+fn datatype_type_params(datatype: &Value) -> Vec<Value> {
+    datatype
+        .get("typeParameters")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|tp| {
+            json!({
+                "constraints": ability_set(tp.get("constraints")),
+                "isPhantom": tp.get("isPhantom").and_then(Value::as_bool).unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+fn normalized_fields(fields: Option<&Value>) -> Vec<Value> {
+    fields
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|f| {
+            json!({
+                "name": f.get("name").cloned().unwrap_or(Value::Null),
+                "type": f
+                    .pointer("/type/signature")
+                    .map(normalized_type)
+                    .unwrap_or(Value::Null),
+            })
+        })
+        .collect()
+}
+
+fn signatures(list: Option<&Value>) -> Vec<Value> {
+    list.and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|p| {
+            p.get("signature")
+                .map(normalized_type)
+                .unwrap_or(Value::Null)
+        })
+        .collect()
+}
+
+/// GraphQL `OpenMoveTypeSignature` (`{"ref": "&mut", "body": ...}`) as a JSON-RPC
+/// `SuiMoveNormalizedType`.
+fn normalized_type(signature: &Value) -> Value {
+    let body = normalized_type_body(signature.get("body").unwrap_or(signature));
+    match signature.get("ref").and_then(Value::as_str) {
+        Some("&") => json!({ "Reference": body }),
+        Some("&mut") => json!({ "MutableReference": body }),
+        _ => body,
+    }
+}
+
+fn normalized_type_body(body: &Value) -> Value {
+    if let Some(primitive) = body.as_str() {
+        return Value::String(pascal_case(primitive));
+    }
+    if let Some(inner) = body.get("vector") {
+        return json!({ "Vector": normalized_type_body(inner) });
+    }
+    if let Some(idx) = body.get("typeParameter") {
+        return json!({ "TypeParameter": idx });
+    }
+    if let Some(datatype) = body.get("datatype") {
+        let type_arguments: Vec<Value> = datatype
+            .get("typeParameters")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(normalized_type_body)
+            .collect();
+        return json!({
+            "Struct": {
+                "address": short_address(
+                    datatype.get("package").and_then(Value::as_str).unwrap_or("")
+                ),
+                "module": datatype.get("module").cloned().unwrap_or(Value::Null),
+                "name": datatype.get("type").cloned().unwrap_or(Value::Null),
+                "typeArguments": type_arguments,
+            }
+        });
+    }
+    body.clone()
+}
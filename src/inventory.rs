@@ -0,0 +1,1712 @@
+//! Interface inventories built from compiled bytecode or RPC normalized modules, plus the
+//! transforms and renderers that operate on them.
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use move_binary_format::file_format::{AbilitySet, SignatureToken, Visibility};
+use move_binary_format::CompiledModule;
+
+use crate::dataset::normalize_package_id;
+
+pub fn canonicalize_json_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let old_map = std::mem::take(map);
+            let mut entries: Vec<(String, Value)> = old_map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            for (_, v) in entries.iter_mut() {
+                canonicalize_json_value(v);
+            }
+
+            for (k, v) in entries {
+                map.insert(k, v);
+            }
+        }
+        Value::Array(values) => {
+            for v in values.iter_mut() {
+                canonicalize_json_value(v);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[derive(Debug, Clone, Serialize, serde::Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TypeSig(pub String);
+
+/// Framework packages whose address is rendered short (`0x2`) in Move type strings.
+const FRAMEWORK_ADDRESSES: &[&str] = &["0x1", "0x2", "0x3", "0xb", "0xdee9"];
+
+fn move_address_display(addr: &str) -> String {
+    let full = normalize_package_id(addr);
+    FRAMEWORK_ADDRESSES
+        .iter()
+        .find(|short| normalize_package_id(short) == full)
+        .map(|short| short.to_string())
+        .unwrap_or(full)
+}
+
+/// Whether `package_id` is one of the framework packages (`0x1`, `0x2`, ...).
+pub fn is_framework_package(package_id: &str) -> bool {
+    let full = normalize_package_id(package_id);
+    FRAMEWORK_ADDRESSES
+        .iter()
+        .any(|short| normalize_package_id(short) == full)
+}
+
+/// `0x2`, `0xdba3...`: the address without leading zeros, as Sui type tags display it.
+fn type_tag_address_display(addr: &str) -> String {
+    let full = normalize_package_id(addr);
+    let digits = full.trim_start_matches("0x").trim_start_matches('0');
+    format!("0x{}", if digits.is_empty() { "0" } else { digits })
+}
+
+impl TypeSig {
+    /// Render in Move syntax (`&mut 0x2::coin::Coin<T0>`, `vector<u8>`). Framework addresses
+    /// are short, others full 64-digit hex. Falls back to the raw JSON for shapes it does
+    /// not recognize.
+    pub fn to_move_string(&self) -> String {
+        self.render_with(move_address_display)
+    }
+
+    /// Render the way Sui type tags display, as typed for PTB type arguments: every address
+    /// without leading zeros (`0x2::coin::Coin<0xdba3...::usdc::USDC>`). Type tags have no
+    /// references or type parameters, so those keep their `&mut ` / `T0` form.
+    pub fn to_type_tag_string(&self) -> String {
+        self.render_with(type_tag_address_display)
+    }
+
+    fn render_with(&self, address: fn(&str) -> String) -> String {
+        serde_json::from_str(&self.0)
+            .ok()
+            .and_then(|v| render_type_sig(&v, address))
+            .unwrap_or_else(|| self.0.clone())
+    }
+}
+
+fn render_type_sig(v: &Value, address: fn(&str) -> String) -> Option<String> {
+    let render = |v: &Value| render_type_sig(v, address);
+    if let Some(s) = v.as_str() {
+        return Some(s.to_lowercase());
+    }
+    if let Some(inner) = v.get("Vector") {
+        return Some(format!("vector<{}>", render(inner)?));
+    }
+    if let Some(inner) = v.get("Reference") {
+        return Some(format!("&{}", render(inner)?));
+    }
+    if let Some(inner) = v.get("MutableReference") {
+        return Some(format!("&mut {}", render(inner)?));
+    }
+    if let Some(idx) = v.get("TypeParameter") {
+        return Some(format!("T{}", idx));
+    }
+    let st = v.get("Struct")?;
+    let mut out = format!(
+        "{}::{}::{}",
+        address(st.get("address")?.as_str()?),
+        st.get("module")?.as_str()?,
+        st.get("name")?.as_str()?
+    );
+    let args = st.get("typeArguments").and_then(Value::as_array);
+    if let Some(args) = args.filter(|a| !a.is_empty()) {
+        let rendered: Option<Vec<String>> = args.iter().map(render).collect();
+        out.push_str(&format!("<{}>", rendered?.join(", ")));
+    }
+    Some(out)
+}
+
+#[derive(Debug, Clone, Serialize, serde::Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FunctionInv {
+    /// Canonical `public` / `friend` / `private` (see `normalize_visibility`); inventories
+    /// saved with other spellings are normalized on load.
+    #[serde(default, deserialize_with = "deserialize_visibility")]
+    pub visibility: Option<String>,
+    pub is_entry: Option<bool>,
+    /// Sorted ability constraints of each type parameter, in declaration order.
+    pub type_params: Vec<Vec<String>>,
+    pub params: Vec<TypeSig>,
+    pub returns: Vec<TypeSig>,
+    /// Some parameter is `&TxContext` or `&mut TxContext` (`0x2::tx_context`).
+    #[serde(default)]
+    pub takes_tx_context: bool,
+}
+
+impl FunctionInv {
+    /// Name-independent Move-style signature, e.g. `fun<T0: drop>(&mut 0x2::coin::Coin<T0>,
+    /// u64): (bool)`; visibility and `entry` are not part of it.
+    pub fn signature(&self) -> String {
+        let type_params: Vec<(bool, Vec<String>)> = self
+            .type_params
+            .iter()
+            .map(|abilities| (false, abilities.clone()))
+            .collect();
+        format!(
+            "fun{}{}: {}",
+            move_type_params_display(&type_params),
+            type_sig_list_display(&self.params),
+            type_sig_list_display(&self.returns)
+        )
+    }
+}
+
+fn takes_tx_context(params: &[TypeSig]) -> bool {
+    params.iter().any(|p| {
+        serde_json::from_str::<Value>(&p.0)
+            .ok()
+            .and_then(|v| {
+                let inner = v.get("MutableReference").or_else(|| v.get("Reference"))?;
+                let st = inner.get("Struct")?;
+                Some(
+                    normalize_package_id(st.get("address")?.as_str()?) == SUI_FRAMEWORK_ADDRESS
+                        && st.get("module")?.as_str()? == "tx_context"
+                        && st.get("name")?.as_str()? == "TxContext",
+                )
+            })
+            .unwrap_or(false)
+    })
+}
+
+#[derive(Debug, Clone, Serialize, serde::Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StructInv {
+    pub abilities: Vec<String>,
+    pub type_params: Option<usize>,
+    /// Phantom marker of each type parameter, in declaration order.
+    #[serde(default)]
+    pub phantom_type_params: Vec<bool>,
+    /// Sorted ability constraints of each type parameter, in declaration order.
+    #[serde(default)]
+    pub type_param_abilities: Vec<Vec<String>>,
+    pub fields: Vec<(String, TypeSig)>,
+    /// Looks like a one-time witness: named after its module in uppercase, `drop` only,
+    /// no type parameters and no fields (bytecode's synthetic `dummy_field: bool` aside).
+    #[serde(default)]
+    pub is_one_time_witness: bool,
+    /// Has `copy` and `drop` but not `key`, the ability shape of Sui event types. This is
+    /// a heuristic only: no `event::emit` call site is checked.
+    #[serde(default)]
+    pub is_event_candidate: bool,
+    /// Has `key` and its first declared field is `0x2::object::UID`. Computed before any
+    /// `--sort-fields` reordering.
+    #[serde(default)]
+    pub is_object: bool,
+}
+
+fn is_object_struct(abilities: &[String], fields: &[(String, TypeSig)]) -> bool {
+    let Some((_, first)) = fields.first() else {
+        return false;
+    };
+    let is_uid = serde_json::from_str::<Value>(&first.0)
+        .ok()
+        .and_then(|v| {
+            let st = v.get("Struct")?;
+            Some(
+                normalize_package_id(st.get("address")?.as_str()?) == SUI_FRAMEWORK_ADDRESS
+                    && st.get("module")?.as_str()? == "object"
+                    && st.get("name")?.as_str()? == "UID",
+            )
+        })
+        .unwrap_or(false);
+    abilities.iter().any(|a| a == "key") && is_uid
+}
+
+fn is_event_candidate(abilities: &[String]) -> bool {
+    let has = |a: &str| abilities.iter().any(|x| x == a);
+    has("copy") && has("drop") && !has("key")
+}
+
+/// One-time witness shape check shared by the bytecode and RPC inventory builders.
+fn is_one_time_witness(
+    module_name: &str,
+    struct_name: &str,
+    abilities: &[String],
+    type_params: usize,
+    fields: &[(String, TypeSig)],
+) -> bool {
+    let no_fields = match fields {
+        [] => true,
+        [(name, ty)] => name == "dummy_field" && ty.0 == "\"Bool\"",
+        _ => false,
+    };
+    struct_name == module_name.to_uppercase()
+        && abilities == ["drop"]
+        && type_params == 0
+        && no_fields
+}
+
+/// Move 2024 enum; variants keyed by name, each with its fields in declaration order.
+#[derive(Debug, Clone, Serialize, serde::Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EnumInv {
+    pub abilities: Vec<String>,
+    pub type_params: Option<usize>,
+    /// Phantom marker of each type parameter, in declaration order.
+    #[serde(default)]
+    pub phantom_type_params: Vec<bool>,
+    /// Sorted ability constraints of each type parameter, in declaration order.
+    #[serde(default)]
+    pub type_param_abilities: Vec<Vec<String>>,
+    pub variants: BTreeMap<String, Vec<(String, TypeSig)>>,
+}
+
+#[derive(Debug, Clone, Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct ModuleInventory {
+    pub module_address: Option<String>,
+    pub functions: BTreeMap<String, FunctionInv>,
+    pub structs: BTreeMap<String, StructInv>,
+    pub enums: BTreeMap<String, EnumInv>,
+}
+
+impl ModuleInventory {
+    /// sha256 hex digest of the module's canonicalized inventory JSON; equal interfaces
+    /// hash equally whatever order their maps were built in.
+    pub fn fingerprint(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let value = serde_json::to_value(self).expect("serialize module inventory");
+        format!("{:x}", Sha256::digest(stable_json(&value).as_bytes()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct PackageInventory {
+    pub modules: BTreeMap<String, ModuleInventory>,
+    /// Normalized modules whose return lists were read from a fallback key rather than
+    /// `return`, module name -> key. Always empty for bytecode inventories.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub return_keys: BTreeMap<String, String>,
+}
+
+impl PackageInventory {
+    /// `ModuleInventory::fingerprint` of every module, keyed by module name.
+    pub fn fingerprints(&self) -> BTreeMap<String, String> {
+        self.modules
+            .iter()
+            .map(|(name, module)| (name.clone(), module.fingerprint()))
+            .collect()
+    }
+}
+
+fn stable_json(v: &Value) -> String {
+    let mut v = v.clone();
+    canonicalize_json_value(&mut v);
+    serde_json::to_string(&v).expect("serialize")
+}
+
+/// Placeholder for a type nested deeper than `InventoryOptions::max_type_depth`; both
+/// inventory builders emit it at the same position, so truncated types still compare equal.
+fn truncated_type(depth: usize) -> Value {
+    json!({"TruncatedType": depth})
+}
+
+/// Cut an RPC type value at `max_depth`, counting nesting the same way
+/// `type_sig_from_token` does.
+fn truncate_type_value(v: &Value, depth: usize, max_depth: usize) -> Value {
+    if depth > max_depth {
+        return truncated_type(depth);
+    }
+    let Some(obj) = v.as_object().filter(|o| o.len() == 1) else {
+        return v.clone();
+    };
+    let (key, inner) = obj.iter().next().expect("one entry");
+    match key.as_str() {
+        "Vector" | "Reference" | "MutableReference" => {
+            json!({ key.as_str(): truncate_type_value(inner, depth + 1, max_depth) })
+        }
+        "Struct" => {
+            let mut st = inner.clone();
+            if let Some(args) = st.get_mut("typeArguments").and_then(Value::as_array_mut) {
+                for arg in args.iter_mut() {
+                    *arg = truncate_type_value(arg, depth + 1, max_depth);
+                }
+            }
+            json!({ "Struct": st })
+        }
+        _ => v.clone(),
+    }
+}
+
+fn type_sig_from_value(v: &Value, max_depth: usize) -> TypeSig {
+    TypeSig(stable_json(&truncate_type_value(v, 0, max_depth)))
+}
+
+fn stable_debug<T: std::fmt::Debug>(v: &T) -> String {
+    format!("{v:?}")
+}
+
+/// Convert SignatureToken to RPC-compatible JSON format.
+/// RPC uses PascalCase primitive types, camelCase keys, and short 0x addresses.
+/// Nesting past `max_depth` is replaced by a `TruncatedType` placeholder.
+fn type_sig_from_token(
+    module: &CompiledModule,
+    token: &SignatureToken,
+    max_depth: usize,
+) -> TypeSig {
+    fn to_rpc_json(
+        module: &CompiledModule,
+        token: &SignatureToken,
+        depth: usize,
+        max_depth: usize,
+    ) -> Value {
+        if depth > max_depth {
+            return truncated_type(depth);
+        }
+        match token {
+            SignatureToken::Bool => Value::String("Bool".to_string()),
+            SignatureToken::U8 => Value::String("U8".to_string()),
+            SignatureToken::U16 => Value::String("U16".to_string()),
+            SignatureToken::U32 => Value::String("U32".to_string()),
+            SignatureToken::U64 => Value::String("U64".to_string()),
+            SignatureToken::U128 => Value::String("U128".to_string()),
+            SignatureToken::U256 => Value::String("U256".to_string()),
+            SignatureToken::Address => Value::String("Address".to_string()),
+            SignatureToken::Signer => Value::String("Signer".to_string()),
+            SignatureToken::Vector(inner) => {
+                json!({"Vector": to_rpc_json(module, inner, depth + 1, max_depth)})
+            }
+            SignatureToken::Datatype(idx) => {
+                let handle = module.datatype_handle_at(*idx);
+                let mod_handle = module.module_handle_at(handle.module);
+                let addr = module
+                    .address_identifier_at(mod_handle.address)
+                    .to_hex_literal();
+                let mod_name = module.identifier_at(mod_handle.name).as_str();
+                let name = module.identifier_at(handle.name).as_str();
+                json!({
+                    "Struct": {
+                        "address": addr,
+                        "module": mod_name,
+                        "name": name,
+                        "typeArguments": []
+                    }
+                })
+            }
+            SignatureToken::DatatypeInstantiation(inst) => {
+                let (idx, type_args) = inst.as_ref();
+                let handle = module.datatype_handle_at(*idx);
+                let mod_handle = module.module_handle_at(handle.module);
+                let addr = module
+                    .address_identifier_at(mod_handle.address)
+                    .to_hex_literal();
+                let mod_name = module.identifier_at(mod_handle.name).as_str();
+                let name = module.identifier_at(handle.name).as_str();
+                let args: Vec<Value> = type_args
+                    .iter()
+                    .map(|t| to_rpc_json(module, t, depth + 1, max_depth))
+                    .collect();
+                json!({
+                    "Struct": {
+                        "address": addr,
+                        "module": mod_name,
+                        "name": name,
+                        "typeArguments": args
+                    }
+                })
+            }
+            SignatureToken::Reference(inner) => {
+                json!({"Reference": to_rpc_json(module, inner, depth + 1, max_depth)})
+            }
+            SignatureToken::MutableReference(inner) => {
+                json!({"MutableReference": to_rpc_json(module, inner, depth + 1, max_depth)})
+            }
+            SignatureToken::TypeParameter(idx) => {
+                json!({"TypeParameter": *idx})
+            }
+        }
+    }
+    TypeSig(stable_json(&to_rpc_json(module, token, 0, max_depth)))
+}
+
+pub fn abilities_to_vec(abilities: &AbilitySet) -> Vec<String> {
+    let mut out = Vec::new();
+    if abilities.has_copy() {
+        out.push("copy".to_string());
+    }
+    if abilities.has_drop() {
+        out.push("drop".to_string());
+    }
+    if abilities.has_key() {
+        out.push("key".to_string());
+    }
+    if abilities.has_store() {
+        out.push("store".to_string());
+    }
+    out.sort();
+    out
+}
+
+/// Map a visibility spelling from either extraction path (`Public`, `FRIEND`,
+/// `public(package)`, ...) to the canonical `public`, `friend` or `private` token, so a
+/// change of casing or wording upstream cannot make equal functions compare unequal.
+pub fn normalize_visibility(visibility: &str) -> &'static str {
+    match visibility.trim().to_ascii_lowercase().as_str() {
+        "public" => "public",
+        "friend" | "package" | "public(friend)" | "public(package)" => "friend",
+        "private" => "private",
+        other => {
+            tracing::warn!(visibility = other, "unrecognized visibility");
+            "unknown"
+        }
+    }
+}
+
+/// Which functions an inventory records, applied identically to bytecode and RPC
+/// normalized modules so the two sides are always selected by the same rule.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum FunctionScope {
+    /// `public` functions, plus `entry` functions of any visibility.
+    PublicEntry,
+    /// `public` and `friend` functions, plus `entry` functions of any visibility. This is
+    /// the set JSON-RPC `exposedFunctions` reports.
+    PublicFriendEntry,
+    /// Every function, including private non-entry ones. RPC never reports those, so
+    /// verifying with this scope flags each of them as missing on the RPC side.
+    All,
+}
+
+impl FunctionScope {
+    /// `visibility` is a canonical token from `normalize_visibility`.
+    pub fn includes(self, visibility: &str, is_entry: bool) -> bool {
+        match self {
+            FunctionScope::PublicEntry => visibility == "public" || is_entry,
+            FunctionScope::PublicFriendEntry => visibility != "private" || is_entry,
+            FunctionScope::All => true,
+        }
+    }
+}
+
+/// What goes into an inventory. Both extraction paths must be built with the same options
+/// for their inventories to compare.
+#[derive(Debug, Clone)]
+pub struct InventoryOptions {
+    pub function_scope: FunctionScope,
+    /// Only modules whose self-name matches are listed or put into an inventory.
+    pub module_filter: Option<Regex>,
+    /// Type nesting past this depth is replaced by a `TruncatedType` placeholder.
+    pub max_type_depth: usize,
+}
+
+impl Default for InventoryOptions {
+    fn default() -> Self {
+        Self {
+            function_scope: FunctionScope::PublicFriendEntry,
+            module_filter: None,
+            max_type_depth: 64,
+        }
+    }
+}
+
+impl InventoryOptions {
+    pub fn module_selected(&self, name: &str) -> bool {
+        self.module_filter
+            .as_ref()
+            .is_none_or(|re| re.is_match(name))
+    }
+}
+
+fn deserialize_visibility<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let visibility: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(visibility.map(|v| normalize_visibility(&v).to_string()))
+}
+
+fn visibility_to_string(v: Visibility) -> String {
+    normalize_visibility(&stable_debug(&v)).to_string()
+}
+
+fn module_inventory_from_compiled_module(
+    m: &CompiledModule,
+    opts: &InventoryOptions,
+) -> ModuleInventory {
+    let mut functions = BTreeMap::new();
+    let mut structs = BTreeMap::new();
+
+    let scope = opts.function_scope;
+    for def in m.function_defs() {
+        let visibility = visibility_to_string(def.visibility);
+        if !scope.includes(&visibility, def.is_entry) {
+            continue;
+        }
+
+        let handle = m.function_handle_at(def.function);
+        let name = m.identifier_at(handle.name).as_str().to_string();
+
+        let visibility = Some(visibility);
+        let is_entry = Some(def.is_entry);
+        let type_params = handle
+            .type_parameters
+            .iter()
+            .map(abilities_to_vec)
+            .collect();
+
+        let params_sig = m.signature_at(handle.parameters);
+        let params = params_sig
+            .0
+            .iter()
+            .map(|t| type_sig_from_token(m, t, opts.max_type_depth))
+            .collect::<Vec<_>>();
+
+        let returns_sig = m.signature_at(handle.return_);
+        let returns = returns_sig
+            .0
+            .iter()
+            .map(|t| type_sig_from_token(m, t, opts.max_type_depth))
+            .collect::<Vec<_>>();
+
+        functions.insert(
+            name,
+            FunctionInv {
+                visibility,
+                is_entry,
+                type_params,
+                takes_tx_context: takes_tx_context(&params),
+                params,
+                returns,
+            },
+        );
+    }
+
+    for def in m.struct_defs() {
+        let handle = m.datatype_handle_at(def.struct_handle);
+        let name = m.identifier_at(handle.name).as_str().to_string();
+        let abilities = abilities_to_vec(&handle.abilities);
+        let type_params = Some(handle.type_parameters.len());
+        let phantom_type_params = handle
+            .type_parameters
+            .iter()
+            .map(|tp| tp.is_phantom)
+            .collect();
+        let type_param_abilities = handle
+            .type_parameters
+            .iter()
+            .map(|tp| abilities_to_vec(&tp.constraints))
+            .collect();
+
+        let mut fields: Vec<(String, TypeSig)> = Vec::new();
+        if let Some(field_info) = def.fields() {
+            for f in field_info {
+                let fname = m.identifier_at(f.name).as_str().to_string();
+                let fty = type_sig_from_token(m, &f.signature.0, opts.max_type_depth);
+                fields.push((fname, fty));
+            }
+        }
+        let otw = is_one_time_witness(
+            m.self_id().name().as_str(),
+            &name,
+            &abilities,
+            handle.type_parameters.len(),
+            &fields,
+        );
+        let event = is_event_candidate(&abilities);
+        let object = is_object_struct(&abilities, &fields);
+
+        structs.insert(
+            name,
+            StructInv {
+                abilities,
+                type_params,
+                phantom_type_params,
+                type_param_abilities,
+                fields,
+                is_one_time_witness: otw,
+                is_event_candidate: event,
+                is_object: object,
+            },
+        );
+    }
+
+    let mut enums = BTreeMap::new();
+    for def in m.enum_defs() {
+        let handle = m.datatype_handle_at(def.enum_handle);
+        let name = m.identifier_at(handle.name).as_str().to_string();
+        let variants = def
+            .variants
+            .iter()
+            .map(|v| {
+                let fields = v
+                    .fields
+                    .iter()
+                    .map(|f| {
+                        (
+                            m.identifier_at(f.name).as_str().to_string(),
+                            type_sig_from_token(m, &f.signature.0, opts.max_type_depth),
+                        )
+                    })
+                    .collect();
+                (m.identifier_at(v.variant_name).as_str().to_string(), fields)
+            })
+            .collect();
+
+        enums.insert(
+            name,
+            EnumInv {
+                abilities: abilities_to_vec(&handle.abilities),
+                type_params: Some(handle.type_parameters.len()),
+                phantom_type_params: handle
+                    .type_parameters
+                    .iter()
+                    .map(|tp| tp.is_phantom)
+                    .collect(),
+                type_param_abilities: handle
+                    .type_parameters
+                    .iter()
+                    .map(|tp| abilities_to_vec(&tp.constraints))
+                    .collect(),
+                variants,
+            },
+        );
+    }
+
+    ModuleInventory {
+        module_address: Some(m.self_id().address().to_hex_literal()),
+        functions,
+        structs,
+        enums,
+    }
+}
+
+pub fn package_inventory_from_compiled_modules(
+    modules: &[impl std::borrow::Borrow<CompiledModule>],
+    opts: &InventoryOptions,
+) -> PackageInventory {
+    let mut out = BTreeMap::new();
+    for m in modules.iter().map(std::borrow::Borrow::borrow) {
+        let name = m.self_id().name().as_str().to_string();
+        if !opts.module_selected(&name) {
+            continue;
+        }
+        out.insert(name, module_inventory_from_compiled_module(m, opts));
+    }
+    PackageInventory {
+        modules: out,
+        return_keys: BTreeMap::new(),
+    }
+}
+
+/// Keys the normalized function's return list may live under, in preference order.
+/// RPC currently uses `return`; the others guard against a future rename silently
+/// turning every function into a zero-return mismatch.
+const RETURN_KEY_CANDIDATES: [&str; 3] = ["return", "returns", "return_"];
+
+/// How one normalized module's return lists were found, so the package can warn once.
+#[derive(Default)]
+struct ReturnKeyUse {
+    /// Fallback key some function's returns were read from.
+    fallback: Option<&'static str>,
+    /// Functions with no return key at all.
+    missing: usize,
+}
+
+/// Find the return list of a normalized function, along with the key it was found under.
+fn normalized_return_value(fval: &Value) -> Option<(&'static str, &Value)> {
+    RETURN_KEY_CANDIDATES
+        .iter()
+        .find_map(|key| fval.get(*key).map(|v| (*key, v)))
+}
+
+/// Lowercased, sorted ability names from an RPC ability set, accepting either the bare
+/// array or the `{"abilities": [...]}` wrapper.
+fn normalized_ability_names(v: &Value) -> Vec<String> {
+    let arr = v
+        .as_array()
+        .or_else(|| v.get("abilities").and_then(Value::as_array));
+    let mut names: Vec<String> = arr
+        .map(|a| {
+            a.iter()
+                .filter_map(Value::as_str)
+                .map(|s| s.to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Phantom marker and ability constraints of each entry of a normalized struct or enum's
+/// `typeParameters`, each entry being `{"constraints": {"abilities": [...]}, "isPhantom": b}`.
+fn normalized_datatype_type_params(datatype: &Value) -> (Vec<bool>, Vec<Vec<String>>) {
+    let params = datatype
+        .get("typeParameters")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let phantom = params
+        .iter()
+        .map(|tp| {
+            tp.get("isPhantom")
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+        })
+        .collect();
+    let abilities = params
+        .iter()
+        .map(|tp| {
+            tp.get("constraints")
+                .map(normalized_ability_names)
+                .unwrap_or_default()
+        })
+        .collect();
+    (phantom, abilities)
+}
+
+fn module_inventory_from_normalized_value(
+    module: &Value,
+    opts: &InventoryOptions,
+    return_keys: &mut ReturnKeyUse,
+) -> Result<ModuleInventory> {
+    let mut functions = BTreeMap::new();
+    let mut structs = BTreeMap::new();
+    let module_name = module.get("name").and_then(Value::as_str).unwrap_or("");
+
+    // RPC uses "exposedFunctions" key, not "functions"
+    let scope = opts.function_scope;
+    if let Some(funcs) = module.get("exposedFunctions").and_then(Value::as_object) {
+        for (fname, fval) in funcs {
+            let visibility = fval
+                .get("visibility")
+                .and_then(Value::as_str)
+                .map(|s| normalize_visibility(s).to_string());
+
+            // RPC uses camelCase: isEntry, typeParameters
+            let is_entry = fval.get("isEntry").and_then(Value::as_bool);
+
+            // A function with no visibility is kept, so the diff can report it.
+            if let Some(ref v) = visibility {
+                if !scope.includes(v, is_entry.unwrap_or(false)) {
+                    continue;
+                }
+            }
+
+            // Each entry is an ability set: {"abilities": ["Copy", ...]}.
+            let type_params = fval
+                .get("typeParameters")
+                .and_then(Value::as_array)
+                .map(|a| a.iter().map(normalized_ability_names).collect())
+                .unwrap_or_default();
+
+            let params: Vec<TypeSig> = fval
+                .get("parameters")
+                .and_then(Value::as_array)
+                .map(|a| {
+                    a.iter()
+                        .map(|v| type_sig_from_value(v, opts.max_type_depth))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let returns = match normalized_return_value(fval) {
+                Some((key, v)) => {
+                    if key != RETURN_KEY_CANDIDATES[0] {
+                        tracing::debug!(function = %fname, key, "fallback return key");
+                        return_keys.fallback = Some(key);
+                    }
+                    v.as_array()
+                        .map(|a| {
+                            a.iter()
+                                .map(|v| type_sig_from_value(v, opts.max_type_depth))
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                }
+                None => {
+                    tracing::debug!(function = %fname, "no return key; assuming no returns");
+                    return_keys.missing += 1;
+                    Vec::new()
+                }
+            };
+
+            functions.insert(
+                fname.clone(),
+                FunctionInv {
+                    visibility,
+                    is_entry,
+                    type_params,
+                    takes_tx_context: takes_tx_context(&params),
+                    params,
+                    returns,
+                },
+            );
+        }
+    }
+
+    if let Some(sobjs) = module.get("structs").and_then(Value::as_object) {
+        for (sname, sval) in sobjs {
+            // RPC uses nested "abilities.abilities" with PascalCase values
+            let mut abilities = sval
+                .get("abilities")
+                .and_then(|v| v.get("abilities"))
+                .and_then(Value::as_array)
+                .map(|a| {
+                    a.iter()
+                        .filter_map(Value::as_str)
+                        .map(|s| s.to_lowercase()) // Normalize to lowercase
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            abilities.sort();
+
+            // RPC uses camelCase: typeParameters
+            let type_params = sval
+                .get("typeParameters")
+                .and_then(Value::as_array)
+                .map(|a| a.len());
+            let (phantom_type_params, type_param_abilities) = normalized_datatype_type_params(sval);
+
+            let mut fields: Vec<(String, TypeSig)> = Vec::new();
+            if let Some(farr) = sval.get("fields").and_then(Value::as_array) {
+                for f in farr {
+                    let fname = f
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or("<unknown>")
+                        .to_string();
+                    let fty = f
+                        .get("type")
+                        .map(|v| type_sig_from_value(v, opts.max_type_depth))
+                        .unwrap_or(TypeSig("null".to_string()));
+                    fields.push((fname, fty));
+                }
+            }
+            let otw = is_one_time_witness(
+                module_name,
+                sname,
+                &abilities,
+                type_params.unwrap_or(0),
+                &fields,
+            );
+            let event = is_event_candidate(&abilities);
+            let object = is_object_struct(&abilities, &fields);
+
+            structs.insert(
+                sname.clone(),
+                StructInv {
+                    abilities,
+                    type_params,
+                    phantom_type_params,
+                    type_param_abilities,
+                    fields,
+                    is_one_time_witness: otw,
+                    is_event_candidate: event,
+                    is_object: object,
+                },
+            );
+        }
+    }
+
+    // Older nodes omit "enums" entirely; treat that as no enums.
+    let mut enums = BTreeMap::new();
+    if let Some(eobjs) = module.get("enums").and_then(Value::as_object) {
+        for (ename, eval) in eobjs {
+            let mut abilities = eval
+                .get("abilities")
+                .and_then(|v| v.get("abilities"))
+                .and_then(Value::as_array)
+                .map(|a| {
+                    a.iter()
+                        .filter_map(Value::as_str)
+                        .map(|s| s.to_lowercase())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            abilities.sort();
+
+            let type_params = eval
+                .get("typeParameters")
+                .and_then(Value::as_array)
+                .map(|a| a.len());
+            let (phantom_type_params, type_param_abilities) = normalized_datatype_type_params(eval);
+
+            let mut variants = BTreeMap::new();
+            if let Some(vobj) = eval.get("variants").and_then(Value::as_object) {
+                for (vname, farr) in vobj {
+                    let fields = farr
+                        .as_array()
+                        .map(|a| {
+                            a.iter()
+                                .map(|f| {
+                                    let fname = f
+                                        .get("name")
+                                        .and_then(Value::as_str)
+                                        .unwrap_or("<unknown>")
+                                        .to_string();
+                                    let fty = f
+                                        .get("type")
+                                        .map(|v| type_sig_from_value(v, opts.max_type_depth))
+                                        .unwrap_or(TypeSig("null".to_string()));
+                                    (fname, fty)
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    variants.insert(vname.clone(), fields);
+                }
+            }
+
+            enums.insert(
+                ename.clone(),
+                EnumInv {
+                    abilities,
+                    type_params,
+                    phantom_type_params,
+                    type_param_abilities,
+                    variants,
+                },
+            );
+        }
+    }
+
+    let module_address = module
+        .get("address")
+        .and_then(Value::as_str)
+        .map(|s| s.to_string());
+
+    Ok(ModuleInventory {
+        module_address,
+        functions,
+        structs,
+        enums,
+    })
+}
+
+pub fn package_inventory_from_normalized_modules(
+    modules_value: &Value,
+    opts: &InventoryOptions,
+) -> Result<PackageInventory> {
+    let mut modules = BTreeMap::new();
+    let mut return_keys = BTreeMap::new();
+    let mut missing_return_keys = 0usize;
+    let Some(mobj) = modules_value.as_object() else {
+        return Err(anyhow!("expected modules to be an object"));
+    };
+
+    for (mname, mval) in mobj {
+        // RPC keys are module names; to compare with local bytecode, key by module self-name.
+        let name = mval
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or(mname)
+            .to_string();
+        if !opts.module_selected(&name) {
+            continue;
+        }
+        let mut key_use = ReturnKeyUse::default();
+        let module = module_inventory_from_normalized_value(mval, opts, &mut key_use)?;
+        if let Some(key) = key_use.fallback {
+            return_keys.insert(name.clone(), key.to_string());
+        }
+        modules.insert(name, module);
+        missing_return_keys += key_use.missing;
+    }
+
+    if !return_keys.is_empty() {
+        tracing::warn!(modules = ?return_keys, "return lists read from a fallback key");
+    }
+    if missing_return_keys > 0 {
+        tracing::warn!(
+            functions = missing_return_keys,
+            tried = ?RETURN_KEY_CANDIDATES,
+            "no return key; assuming no returns"
+        );
+    }
+    Ok(PackageInventory {
+        modules,
+        return_keys,
+    })
+}
+
+/// Re-key modules per `renames` (old name -> new name). Names inside type signatures are
+/// left alone, so references to a renamed module's types still show up as diffs.
+pub fn rename_inventory_modules(inv: &mut PackageInventory, renames: &BTreeMap<String, String>) {
+    // Take all renamed modules out first so swaps (a -> b, b -> a) work.
+    let moved: Vec<(String, ModuleInventory)> = renames
+        .iter()
+        .filter_map(|(old, new)| inv.modules.remove(old).map(|m| (new.clone(), m)))
+        .collect();
+    inv.modules.extend(moved);
+}
+
+/// `fooBar` / `FooBar` -> `foo_bar`; already snake_case names are returned unchanged.
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut prev_lower_or_digit = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower_or_digit {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            prev_lower_or_digit = false;
+        } else {
+            out.push(c);
+            prev_lower_or_digit = c.is_ascii_lowercase() || c.is_ascii_digit();
+        }
+    }
+    out
+}
+
+/// Snake-case every struct field name in place, returning `module::Struct::old -> new`
+/// for each name that changed.
+pub fn normalize_struct_field_names(inv: &mut PackageInventory) -> Vec<String> {
+    let mut renamed = Vec::new();
+    for (mname, module) in inv.modules.iter_mut() {
+        for (sname, s) in module.structs.iter_mut() {
+            for (fname, _) in s.fields.iter_mut() {
+                let normalized = to_snake_case(fname);
+                if normalized != *fname {
+                    renamed.push(format!("{}::{}::{} -> {}", mname, sname, fname, normalized));
+                    *fname = normalized;
+                }
+            }
+        }
+    }
+    renamed
+}
+
+const SELF_ADDRESS_PLACEHOLDER: &str = "0xSELF";
+
+/// Replace `Struct.address` values found in `self_addrs` (normalized ids) with
+/// `SELF_ADDRESS_PLACEHOLDER`, anywhere inside the signature.
+fn rewrite_type_sig_self_address(sig: &mut TypeSig, self_addrs: &BTreeSet<String>) {
+    fn walk(v: &mut Value, self_addrs: &BTreeSet<String>) {
+        match v {
+            Value::Object(map) => {
+                if let Some(Value::Object(st)) = map.get_mut("Struct") {
+                    if let Some(Value::String(addr)) = st.get_mut("address") {
+                        if self_addrs.contains(&normalize_package_id(addr)) {
+                            *addr = SELF_ADDRESS_PLACEHOLDER.to_string();
+                        }
+                    }
+                }
+                for child in map.values_mut() {
+                    walk(child, self_addrs);
+                }
+            }
+            Value::Array(values) => {
+                for child in values {
+                    walk(child, self_addrs);
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Ok(mut v) = serde_json::from_str::<Value>(&sig.0) {
+        walk(&mut v, self_addrs);
+        *sig = TypeSig(stable_json(&v));
+    }
+}
+
+pub fn rewrite_self_addresses(inv: &mut PackageInventory, self_addrs: &BTreeSet<String>) {
+    for module in inv.modules.values_mut() {
+        for f in module.functions.values_mut() {
+            for sig in f.params.iter_mut().chain(f.returns.iter_mut()) {
+                rewrite_type_sig_self_address(sig, self_addrs);
+            }
+        }
+        for st in module.structs.values_mut() {
+            for (_, sig) in st.fields.iter_mut() {
+                rewrite_type_sig_self_address(sig, self_addrs);
+            }
+        }
+        for en in module.enums.values_mut() {
+            for fields in en.variants.values_mut() {
+                for (_, sig) in fields.iter_mut() {
+                    rewrite_type_sig_self_address(sig, self_addrs);
+                }
+            }
+        }
+    }
+}
+
+/// Sort every struct's fields by name, for `--sort-fields` comparisons that only care
+/// about the field set and not declaration order.
+pub fn sort_struct_fields(inv: &mut PackageInventory) {
+    for module in inv.modules.values_mut() {
+        for s in module.structs.values_mut() {
+            s.fields.sort();
+        }
+    }
+}
+
+pub fn type_sig_list_display(sigs: &[TypeSig]) -> String {
+    let parts: Vec<String> = sigs.iter().map(TypeSig::to_move_string).collect();
+    format!("({})", parts.join(", "))
+}
+
+/// `<T0: copy + drop, phantom T1>`, or empty when there are no type parameters.
+fn move_type_params_display(params: &[(bool, Vec<String>)]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+    let rendered: Vec<String> = params
+        .iter()
+        .enumerate()
+        .map(|(i, (phantom, abilities))| {
+            let mut p = format!("{}T{}", if *phantom { "phantom " } else { "" }, i);
+            if !abilities.is_empty() {
+                p.push_str(&format!(": {}", abilities.join(" + ")));
+            }
+            p
+        })
+        .collect();
+    format!("<{}>", rendered.join(", "))
+}
+
+/// `(phantom, constraints)` of each of a struct or enum's `count` type parameters, for
+/// `move_type_params_display`. Markers missing from older inventories default to none.
+fn datatype_type_params(
+    count: Option<usize>,
+    phantom: &[bool],
+    abilities: &[Vec<String>],
+) -> Vec<(bool, Vec<String>)> {
+    (0..count.unwrap_or(0))
+        .map(|i| {
+            (
+                phantom.get(i).copied().unwrap_or(false),
+                abilities.get(i).cloned().unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+fn move_abilities_clause(abilities: &[String]) -> String {
+    if abilities.is_empty() {
+        String::new()
+    } else {
+        format!(" has {}", abilities.join(", "))
+    }
+}
+
+fn move_fields_display(fields: &[(String, TypeSig)], indent: &str) -> String {
+    fields
+        .iter()
+        .map(|(name, ty)| format!("{}{}: {},\n", indent, name, ty.to_move_string()))
+        .collect()
+}
+
+/// Render each module of `pkg` as a Move source stub: struct and enum declarations with
+/// their fields, and `native` declarations for the functions in the inventory.
+pub fn render_move_stub(pkg: &PackageInventory) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::new();
+    for (module_name, m) in &pkg.modules {
+        let mut src = format!(
+            "module {}::{} {{\n",
+            m.module_address.as_deref().unwrap_or("0x0"),
+            module_name
+        );
+
+        for (name, st) in &m.structs {
+            let params = datatype_type_params(
+                st.type_params,
+                &st.phantom_type_params,
+                &st.type_param_abilities,
+            );
+            src.push_str(&format!(
+                "    public struct {}{}{} {{\n{}    }}\n\n",
+                name,
+                move_type_params_display(&params),
+                move_abilities_clause(&st.abilities),
+                move_fields_display(&st.fields, "        ")
+            ));
+        }
+
+        for (name, en) in &m.enums {
+            let params = datatype_type_params(
+                en.type_params,
+                &en.phantom_type_params,
+                &en.type_param_abilities,
+            );
+            src.push_str(&format!(
+                "    public enum {}{}{} {{\n",
+                name,
+                move_type_params_display(&params),
+                move_abilities_clause(&en.abilities)
+            ));
+            for (variant, fields) in &en.variants {
+                if fields.is_empty() {
+                    src.push_str(&format!("        {},\n", variant));
+                } else {
+                    src.push_str(&format!(
+                        "        {} {{\n{}        }},\n",
+                        variant,
+                        move_fields_display(fields, "            ")
+                    ));
+                }
+            }
+            src.push_str("    }\n\n");
+        }
+
+        for (name, f) in &m.functions {
+            let modifiers = match (f.visibility.as_deref(), f.is_entry == Some(true)) {
+                (Some("public"), true) => "public entry ",
+                (Some("public"), false) => "public ",
+                (Some("friend"), true) => "public(package) entry ",
+                (Some("friend"), false) => "public(package) ",
+                (_, true) => "entry ",
+                (_, false) => "",
+            };
+            let params: Vec<(bool, Vec<String>)> = f
+                .type_params
+                .iter()
+                .map(|abilities| (false, abilities.clone()))
+                .collect();
+            let args: Vec<String> = f
+                .params
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| format!("a{}: {}", i, ty.to_move_string()))
+                .collect();
+            let ret = match f.returns.as_slice() {
+                [] => String::new(),
+                [one] => format!(": {}", one.to_move_string()),
+                many => format!(
+                    ": ({})",
+                    many.iter()
+                        .map(TypeSig::to_move_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            };
+            src.push_str(&format!(
+                "    {}native fun {}{}({}){};\n",
+                modifiers,
+                name,
+                move_type_params_display(&params),
+                args.join(", "),
+                ret
+            ));
+        }
+
+        src.push_str("}\n");
+        out.insert(module_name.clone(), src);
+    }
+    out
+}
+
+/// TypeScript type for a Move field type. Structs of `self_address` in another module are
+/// recorded in `imports`; common framework types get fixed mappings; other foreign
+/// structs become `unknown`.
+fn ts_type(
+    v: &Value,
+    self_address: &str,
+    module: &str,
+    imports: &mut BTreeSet<(String, String)>,
+) -> String {
+    if let Some(prim) = v.as_str() {
+        return match prim {
+            "U8" | "U16" | "U32" | "U64" => "number | string",
+            "U128" | "U256" | "Address" | "Signer" => "string",
+            "Bool" => "boolean",
+            _ => "unknown",
+        }
+        .to_string();
+    }
+    if let Some(inner) = v.get("Vector") {
+        let elem = ts_type(inner, self_address, module, imports);
+        return if elem.contains(' ') {
+            format!("({})[]", elem)
+        } else {
+            format!("{}[]", elem)
+        };
+    }
+    if let Some(idx) = v.get("TypeParameter") {
+        return format!("T{}", idx);
+    }
+    let Some(st) = v.get("Struct") else {
+        return "unknown".to_string();
+    };
+    let address = normalize_package_id(st.get("address").and_then(Value::as_str).unwrap_or(""));
+    let st_module = st.get("module").and_then(Value::as_str).unwrap_or("");
+    let name = st.get("name").and_then(Value::as_str).unwrap_or("");
+    let args: Vec<String> = st
+        .get("typeArguments")
+        .and_then(Value::as_array)
+        .map(|a| {
+            a.iter()
+                .map(|t| ts_type(t, self_address, module, imports))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if address == self_address {
+        if st_module != module {
+            imports.insert((st_module.to_string(), name.to_string()));
+        }
+        return if args.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}<{}>", name, args.join(", "))
+        };
+    }
+    match (address.as_str(), st_module, name) {
+        (MOVE_STDLIB_ADDRESS, "string" | "ascii", "String") => "string".to_string(),
+        (MOVE_STDLIB_ADDRESS, "option", "Option") if args.len() == 1 => {
+            format!("{} | null", args[0])
+        }
+        (SUI_FRAMEWORK_ADDRESS, "object", "UID") => "{ id: string }".to_string(),
+        (SUI_FRAMEWORK_ADDRESS, "object", "ID") => "string".to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+const MOVE_STDLIB_ADDRESS: &str =
+    "0x0000000000000000000000000000000000000000000000000000000000000001";
+const SUI_FRAMEWORK_ADDRESS: &str =
+    "0x0000000000000000000000000000000000000000000000000000000000000002";
+
+/// Render one `.ts` source per module with an exported interface for each struct.
+pub fn render_ts_definitions(package_id: &str, pkg: &PackageInventory) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::new();
+    for (module_name, m) in &pkg.modules {
+        let self_address = normalize_package_id(m.module_address.as_deref().unwrap_or(package_id));
+        let mut imports = BTreeSet::new();
+        let mut body = String::new();
+        for (name, st) in &m.structs {
+            let params: Vec<String> = (0..st.type_params.unwrap_or(0))
+                .map(|i| format!("T{}", i))
+                .collect();
+            let generics = if params.is_empty() {
+                String::new()
+            } else {
+                format!("<{}>", params.join(", "))
+            };
+            body.push_str(&format!("export interface {}{} {{\n", name, generics));
+            for (field, ty) in &st.fields {
+                let ts = serde_json::from_str::<Value>(&ty.0)
+                    .map(|v| ts_type(&v, &self_address, module_name, &mut imports))
+                    .unwrap_or_else(|_| "unknown".to_string());
+                body.push_str(&format!("  {}: {};\n", field, ts));
+            }
+            body.push_str("}\n\n");
+        }
+
+        let mut src = format!(
+            "// Generated from package {} module {}.\n\n",
+            package_id, module_name
+        );
+        let mut by_module: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (module, name) in &imports {
+            by_module.entry(module).or_default().push(name);
+        }
+        for (module, names) in &by_module {
+            src.push_str(&format!(
+                "import type {{ {} }} from \"./{}\";\n",
+                names.join(", "),
+                module
+            ));
+        }
+        if !by_module.is_empty() {
+            src.push('\n');
+        }
+        src.push_str(body.trim_end());
+        src.push('\n');
+        out.insert(module_name.clone(), src);
+    }
+    out
+}
+
+/// Load a normalized-modules JSON file (as returned by
+/// `sui_getNormalizedMoveModulesByPackage`) into a `PackageInventory`.
+pub fn package_inventory_from_normalized_modules_file(
+    path: &Path,
+    opts: &InventoryOptions,
+) -> Result<PackageInventory> {
+    let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let mut value: Value =
+        serde_json::from_str(&text).with_context(|| format!("parse json {}", path.display()))?;
+    // Accept a raw JSON-RPC response envelope as well as the bare module map.
+    if value.get("jsonrpc").is_some() {
+        if let Some(result) = value.get_mut("result") {
+            value = result.take();
+        }
+    }
+    canonicalize_json_value(&mut value);
+    package_inventory_from_normalized_modules(&value, opts)
+        .with_context(|| format!("parse normalized modules {}", path.display()))
+}
+
+/// Load an inventory previously written by `--emit-inventory-json`.
+pub fn load_package_inventory(path: &Path) -> Result<PackageInventory> {
+    let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("parse inventory {}", path.display()))
+}
+
+/// `module::function<T0: drop>(param, ...) -> (return, ...)` for every public function,
+/// with types in `TypeSig::to_type_tag_string` form.
+pub fn public_function_signatures(inv: &PackageInventory) -> Vec<String> {
+    let list = |sigs: &[TypeSig]| {
+        sigs.iter()
+            .map(TypeSig::to_type_tag_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut out = Vec::new();
+    for (mname, m) in &inv.modules {
+        for (fname, f) in &m.functions {
+            if f.visibility.as_deref() != Some("public") {
+                continue;
+            }
+            let type_params: Vec<(bool, Vec<String>)> =
+                f.type_params.iter().map(|a| (false, a.clone())).collect();
+            out.push(format!(
+                "{}::{}{}({}) -> ({})",
+                mname,
+                fname,
+                move_type_params_display(&type_params),
+                list(&f.params),
+                list(&f.returns)
+            ));
+        }
+    }
+    out
+}
+
+/// Every function/struct/enum of an inventory as `kind module::name` -> serialized item,
+/// so three inventories can be compared item by item.
+pub fn inventory_items(inv: &PackageInventory) -> BTreeMap<String, String> {
+    let mut items = BTreeMap::new();
+    for (mname, m) in &inv.modules {
+        for (name, f) in &m.functions {
+            items.insert(
+                format!("function {}::{}", mname, name),
+                serde_json::to_string(f).expect("serialize"),
+            );
+        }
+        for (name, st) in &m.structs {
+            items.insert(
+                format!("struct {}::{}", mname, name),
+                serde_json::to_string(st).expect("serialize"),
+            );
+        }
+        for (name, en) in &m.enums {
+            items.insert(
+                format!("enum {}::{}", mname, name),
+                serde_json::to_string(en).expect("serialize"),
+            );
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::file_format::{
+        empty_module, Ability, DatatypeHandle, DatatypeHandleIndex, FieldDefinition,
+        FunctionDefinition, FunctionHandle, FunctionHandleIndex, IdentifierIndex,
+        ModuleHandleIndex, Signature, SignatureIndex, StructDefinition, StructFieldInformation,
+        TypeSignature,
+    };
+    use move_core_types::identifier::Identifier;
+
+    /// An empty module `name` at address `0x0`.
+    fn module(name: &str) -> CompiledModule {
+        let mut m = empty_module();
+        m.identifiers[0] = Identifier::new(name).unwrap();
+        m
+    }
+
+    fn identifier(m: &mut CompiledModule, name: &str) -> IdentifierIndex {
+        m.identifiers.push(Identifier::new(name).unwrap());
+        IdentifierIndex((m.identifiers.len() - 1) as u16)
+    }
+
+    fn add_struct(
+        m: &mut CompiledModule,
+        name: &str,
+        abilities: AbilitySet,
+        fields: &[(&str, SignatureToken)],
+    ) {
+        let name = identifier(m, name);
+        m.datatype_handles.push(DatatypeHandle {
+            module: ModuleHandleIndex(0),
+            name,
+            abilities,
+            type_parameters: Vec::new(),
+        });
+        let fields = fields
+            .iter()
+            .map(|(field, ty)| FieldDefinition {
+                name: identifier(m, field),
+                signature: TypeSignature(ty.clone()),
+            })
+            .collect();
+        m.struct_defs.push(StructDefinition {
+            struct_handle: DatatypeHandleIndex((m.datatype_handles.len() - 1) as u16),
+            field_information: StructFieldInformation::Declared(fields),
+        });
+    }
+
+    fn signature(m: &mut CompiledModule, tokens: Vec<SignatureToken>) -> SignatureIndex {
+        m.signatures.push(Signature(tokens));
+        SignatureIndex((m.signatures.len() - 1) as u16)
+    }
+
+    fn add_function(
+        m: &mut CompiledModule,
+        name: &str,
+        visibility: Visibility,
+        params: Vec<SignatureToken>,
+        returns: Vec<SignatureToken>,
+    ) {
+        let handle = FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: identifier(m, name),
+            parameters: signature(m, params),
+            return_: signature(m, returns),
+            type_parameters: Vec::new(),
+        };
+        m.function_handles.push(handle);
+        m.function_defs.push(FunctionDefinition {
+            function: FunctionHandleIndex((m.function_handles.len() - 1) as u16),
+            visibility,
+            is_entry: false,
+            acquires_global_resources: Vec::new(),
+            code: None,
+        });
+    }
+
+    #[test]
+    fn compiled_module_structs_are_counted() {
+        let mut m = module("pool");
+        add_struct(
+            &mut m,
+            "Pool",
+            AbilitySet::singleton(Ability::Key),
+            &[("balance", SignatureToken::U64)],
+        );
+        add_struct(
+            &mut m,
+            "Receipt",
+            AbilitySet::singleton(Ability::Drop),
+            &[("amount", SignatureToken::U64)],
+        );
+
+        let inv = package_inventory_from_compiled_modules(&[m], &InventoryOptions::default());
+        let structs = &inv.modules["pool"].structs;
+        assert_eq!(structs.len(), 2);
+        assert!(structs.contains_key("Pool") && structs.contains_key("Receipt"));
+    }
+
+    #[test]
+    fn only_the_one_time_witness_is_flagged() {
+        let drop = AbilitySet::singleton(Ability::Drop);
+        let dummy = ("dummy_field", SignatureToken::Bool);
+        // (module, struct, abilities, fields, expected flag)
+        let cases = [
+            ("coin_a", "COIN_A", drop, vec![dummy.clone()], true),
+            ("coin_b", "WITNESS", drop, vec![dummy.clone()], false),
+            (
+                "coin_c",
+                "COIN_C",
+                drop | Ability::Store,
+                vec![dummy.clone()],
+                false,
+            ),
+            (
+                "coin_d",
+                "COIN_D",
+                drop,
+                vec![dummy.clone(), ("amount", SignatureToken::U64)],
+                false,
+            ),
+        ];
+        let modules: Vec<CompiledModule> = cases
+            .iter()
+            .map(|(module_name, struct_name, abilities, fields, _)| {
+                let mut m = module(module_name);
+                add_struct(&mut m, struct_name, *abilities, fields);
+                m
+            })
+            .collect();
+
+        let inv = package_inventory_from_compiled_modules(&modules, &InventoryOptions::default());
+        for (module_name, struct_name, _, _, expected) in &cases {
+            let st = &inv.modules[*module_name].structs[*struct_name];
+            assert_eq!(
+                st.is_one_time_witness, *expected,
+                "{module_name}::{struct_name}"
+            );
+        }
+    }
+
+    #[test]
+    fn friend_functions_match_across_extraction_paths() {
+        let mut m = module("pool");
+        add_function(
+            &mut m,
+            "settle",
+            Visibility::Friend,
+            vec![SignatureToken::U64],
+            vec![SignatureToken::Bool],
+        );
+        add_function(
+            &mut m,
+            "open",
+            Visibility::Public,
+            Vec::new(),
+            vec![SignatureToken::U64],
+        );
+        let normalized = json!({
+            "pool": {
+                "address": "0x0",
+                "name": "pool",
+                "exposedFunctions": {
+                    "settle": {
+                        "visibility": "Friend",
+                        "isEntry": false,
+                        "typeParameters": [],
+                        "parameters": ["U64"],
+                        "return": ["Bool"]
+                    },
+                    "open": {
+                        "visibility": "Public",
+                        "isEntry": false,
+                        "typeParameters": [],
+                        "parameters": [],
+                        "return": ["U64"]
+                    }
+                },
+                "structs": {}
+            }
+        });
+
+        let opts = InventoryOptions::default();
+        let compiled = package_inventory_from_compiled_modules(&[m], &opts);
+        let rpc = package_inventory_from_normalized_modules(&normalized, &opts).unwrap();
+        assert_eq!(
+            compiled.modules["pool"].functions["settle"]
+                .visibility
+                .as_deref(),
+            Some("friend")
+        );
+        let diff = crate::diff::diff_package_inventory(&compiled, &rpc, true);
+        assert!(diff.is_empty(), "{:?}", diff.diffs);
+    }
+
+    #[test]
+    fn deep_nested_vectors_truncate_at_the_depth_cap() {
+        const NESTING: usize = 1_000;
+        let mut token = SignatureToken::U8;
+        let mut value = json!("U8");
+        for _ in 0..NESTING {
+            token = SignatureToken::Vector(Box::new(token));
+            value = json!({ "Vector": value });
+        }
+
+        let expected = TypeSig(
+            r#"{"Vector":{"Vector":{"Vector":{"Vector":{"TruncatedType":4}}}}}"#.to_string(),
+        );
+        let m = module("deep");
+        assert_eq!(type_sig_from_token(&m, &token, 3), expected);
+        assert_eq!(type_sig_from_value(&value, 3), expected);
+    }
+}
@@ -0,0 +1,8 @@
+//! Extract Move package interfaces from bytecode and verify them against RPC normalized
+//! modules. The `sui-move-interface-extractor2` binary is a CLI over these modules.
+
+pub mod dataset;
+pub mod diff;
+pub mod graphql;
+pub mod inventory;
+pub mod rpc;
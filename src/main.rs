@@ -1,24 +1,166 @@
 use anyhow::{anyhow, Context, Result};
 use clap::{Parser, ValueEnum};
+use futures::StreamExt;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 use serde::Serialize;
 use serde_json::{json, Value};
 use std::collections::btree_map;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use move_binary_format::file_format::{AbilitySet, SignatureToken, Visibility};
+use move_binary_format::file_format::{SignatureToken, Visibility};
 use move_binary_format::CompiledModule;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::ModuleId;
+use move_stackless_bytecode_2::ast::Instruction;
 use move_stackless_bytecode_2::from_compiled_modules;
-use sui_sdk::types::base_types::ObjectID;
 
-#[derive(Debug, Copy, Clone, ValueEnum)]
-enum MvrNetwork {
+use sui_move_interface_extractor2::dataset::{
+    artifact_dir_in_sui_packages, default_dataset_root,
+    extract_module_names_from_bytecode_modules_dir, iter_dataset_package_ids,
+    load_compiled_modules_from_bytecode_modules_dir, normalize_package_id, prepare_related_ids,
+    read_metadata_string_field, read_package_keyed_json, resolve_original_package_id,
+    sui_packages_artifact_dir_for_package_id, try_load_local_modules_for_package, DatasetOptions,
+    MvrNetwork,
+};
+use sui_move_interface_extractor2::diff::{
+    classify_three_way, compare_normalized_files, describe_module_inventory_diff,
+    diff_package_inventory, interface_regressions, module_addresses_differ, package_diff,
+    DiffEntry, PackageInventoryDiff, Severity, StructDiff,
+};
+use sui_move_interface_extractor2::inventory::{
+    abilities_to_vec, canonicalize_json_value, inventory_items, load_package_inventory,
+    normalize_struct_field_names, package_inventory_from_compiled_modules,
+    package_inventory_from_normalized_modules, public_function_signatures,
+    rename_inventory_modules, render_move_stub, render_ts_definitions, rewrite_self_addresses,
+    sort_struct_fields, FunctionScope, InventoryOptions, PackageInventory,
+};
+use sui_move_interface_extractor2::rpc::{
+    fetch_rpc_package_inventory, fetch_source_digest_via_rpc, fetch_upgrade_policy_via_rpc,
+    is_rpc_timeout, load_compiled_modules_with_rpc_deps, load_root_package_modules,
+    object_id_from_hex_str, resolve_mvr_name, resolve_upgrade_chain, rpc_metrics, DepGraph,
+    DependencyCycle, RpcClient, RpcKind, RpcMetrics, RpcRetryPolicy, RpcTimeouts,
+    FAIL_ON_DEP_CYCLE, MAX_DEP_DEPTH, RPC_CACHE_DIR, RPC_CONCURRENCY, RPC_RETRY_POLICY,
+    RPC_TIMEOUTS,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn as_filter(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+/// `--network` preset for the RPC URL and, for public networks, the dataset.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Network {
     Mainnet,
     Testnet,
+    Devnet,
+    /// A `sui start` network on this machine; has no dataset.
+    Localnet,
+}
+
+impl Network {
+    fn dataset_network(self) -> Option<MvrNetwork> {
+        match self {
+            Network::Mainnet => Some(MvrNetwork::Mainnet),
+            Network::Testnet => Some(MvrNetwork::Testnet),
+            Network::Devnet => Some(MvrNetwork::Devnet),
+            Network::Localnet => None,
+        }
+    }
+
+    fn rpc_url(self, kind: RpcKind) -> &'static str {
+        match (self.dataset_network(), kind) {
+            (Some(n), RpcKind::Jsonrpc) => n.default_rpc_url(),
+            (Some(n), RpcKind::Graphql) => n.default_graphql_url(),
+            (None, RpcKind::Jsonrpc) => "http://127.0.0.1:9000",
+            (None, RpcKind::Graphql) => "http://127.0.0.1:9125/graphql",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum SummaryFormat {
+    Jsonl,
+    Csv,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum IndexFormat {
+    /// `meta.json`, `by_package_id.json`, `errors.json` and `by_function_sig.json`.
+    Json,
+    /// A single `index.db` with `packages` and `errors` tables.
+    Sqlite,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum VerifyReportFormat {
+    Jsonl,
+    Text,
+}
+
+/// `--shard I/N`: keep the ids at positions `i` with `i % N == I`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Shard {
+    index: usize,
+    count: usize,
+}
+
+impl std::str::FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (index, count) = s
+            .split_once('/')
+            .ok_or_else(|| format!("expected I/N, got {s:?}"))?;
+        let index: usize = index
+            .trim()
+            .parse()
+            .map_err(|e| format!("shard index {index:?}: {e}"))?;
+        let count: usize = count
+            .trim()
+            .parse()
+            .map_err(|e| format!("shard count {count:?}: {e}"))?;
+        if index >= count {
+            return Err(format!(
+                "shard index {index} must be below shard count {count}"
+            ));
+        }
+        Ok(Shard { index, count })
+    }
+}
+
+impl Shard {
+    fn select<T>(self, items: Vec<T>) -> Vec<T> {
+        items
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| i % self.count == self.index)
+            .map(|(_, item)| item)
+            .collect()
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -36,22 +178,112 @@ struct Args {
     #[arg(long, value_name = "PATH")]
     package_ids_file: Option<PathBuf>,
 
+    /// Read additional ids from stdin (same format as --package-ids-file).
+    #[arg(long)]
+    package_ids_stdin: bool,
+
     /// Read ids from an MVR catalog.json (uses *package_info_id fields).
     #[arg(long, value_name = "PATH")]
     mvr_catalog: Option<PathBuf>,
 
-    /// Which MVR catalog id field to use.
-    #[arg(long, value_enum, default_value_t = MvrNetwork::Mainnet)]
-    mvr_network: MvrNetwork,
+    /// MVR name (e.g. `@namespace/app`) to resolve to a package id via the MVR resolver
+    /// of `--mvr-network`. Can be provided multiple times.
+    #[arg(long, value_name = "NAME")]
+    mvr_name: Vec<String>,
+
+    /// Which MVR catalog id field / resolver to use (default: `--dataset-network`).
+    #[arg(long, value_enum)]
+    mvr_network: Option<MvrNetwork>,
+
+    /// Network of the local dataset: selects `sui-packages/packages/<network>_most_used`
+    /// and, unless overridden, the MVR catalog field and RPC URL (default: `--network`,
+    /// else mainnet).
+    #[arg(long, value_enum)]
+    dataset_network: Option<MvrNetwork>,
 
-    /// RPC URL (default: mainnet fullnode)
-    #[arg(long, default_value = "https://fullnode.mainnet.sui.io:443")]
-    rpc_url: String,
+    /// Preset for the default `--rpc-url` (fullnode or GraphQL service per `--rpc-kind`) and,
+    /// except for localnet, `--dataset-network`. Explicit flags still win.
+    #[arg(long, value_enum)]
+    network: Option<Network>,
+
+    /// Root of the `sui-packages` checkout (default: `$SUI_PACKAGES_DIR`, else
+    /// `../sui-packages`).
+    #[arg(long, value_name = "PATH")]
+    dataset_dir: Option<PathBuf>,
+
+    /// Log and skip local `.mv` files that fail to deserialize instead of failing the
+    /// package; the number skipped is reported as `skipped_modules`.
+    #[arg(long)]
+    skip_bad_modules: bool,
+
+    /// Fail a package whose local `bytecode_modules` dir holds modules of more than one
+    /// self address, instead of logging a warning.
+    #[arg(long)]
+    strict: bool,
+
+    /// After a package fails stackless translation, retranslate each of its modules with
+    /// only its dependencies to find the failing ones (`stackless_failed_modules`). Slow.
+    #[arg(long)]
+    isolate_stackless_failures: bool,
+
+    /// Log verbosity on stderr; `RUST_LOG`, when set, takes precedence.
+    #[arg(long, value_enum, default_value_t = LogLevel::Info)]
+    log_level: LogLevel,
+
+    /// RPC URL (default: fullnode of `--network`, else of `--dataset-network`, or its GraphQL
+    /// service with `--rpc-kind graphql`)
+    #[arg(long)]
+    rpc_url: Option<String>,
+
+    /// Which functions inventories record, on both the bytecode and the RPC side.
+    #[arg(long, value_enum, default_value_t = FunctionScope::PublicFriendEntry)]
+    function_scope: FunctionScope,
+
+    /// Only list and inventory modules whose name matches this regex (on both the
+    /// bytecode and the RPC side).
+    #[arg(long, value_name = "REGEX", value_parser = regex::Regex::new)]
+    module_filter: Option<regex::Regex>,
+
+    /// Type nesting depth (vectors, references, type arguments) past which a signature is
+    /// replaced by a `{"TruncatedType": depth}` placeholder instead of rendered further.
+    #[arg(long, default_value_t = 64)]
+    max_type_depth: usize,
+
+    /// API used for package reads. `graphql` supports everything except
+    /// `--check-source-digest` and `--fetch-upgrade-policy`.
+    #[arg(long, value_enum, default_value_t = RpcKind::Jsonrpc)]
+    rpc_kind: RpcKind,
 
     /// Write a batch summary as JSONL.
     #[arg(long, value_name = "PATH")]
     summary_jsonl: Option<PathBuf>,
 
+    /// Merge these summary JSONL files (repeatable) into `--summary-jsonl`, keeping the
+    /// last row for each `resolved_package_id`.
+    #[arg(long, value_name = "PATH")]
+    merge_summaries: Vec<PathBuf>,
+
+    /// Format of the batch summary written to `--summary-jsonl`.
+    #[arg(long, value_enum, default_value_t = SummaryFormat::Jsonl)]
+    summary_format: SummaryFormat,
+
+    /// Continue an interrupted batch run: keep the rows already written (to the
+    /// `--summary-jsonl` path plus `.tmp` until the run completes), skip their packages, and
+    /// append the rest.
+    #[arg(long, default_value_t = false)]
+    resume: bool,
+
+    /// In batch mode, process only shard I of N (0-based) of the sorted dataset ids, e.g.
+    /// `--shard 3/8`. Give each shard its own `--summary-jsonl` and combine them with
+    /// `--merge-summaries`.
+    #[arg(long, value_name = "I/N")]
+    shard: Option<Shard>,
+
+    /// After a batch run, write aggregate stats (package, ok, error and module counts plus a
+    /// `stackless_error` histogram) to this JSON file.
+    #[arg(long, value_name = "PATH")]
+    batch_stats_out: Option<PathBuf>,
+
     /// Limit the number of packages processed.
     #[arg(long, value_name = "N")]
     max_packages: Option<usize>,
@@ -60,10 +292,21 @@ struct Args {
     #[arg(long, default_value_t = false)]
     list_modules: bool,
 
-    /// Batch-run local bytecode extraction over entries in `sui-packages/packages/mainnet_most_used`.
+    /// Batch-run local bytecode extraction over every dataset entry
+    /// (`sui-packages/packages/<--dataset-network>_most_used`; the flag name predates testnet).
     #[arg(long, default_value_t = false)]
     batch_local_bytecode_mainnet_most_used: bool,
 
+    /// In batch mode, add each package's `function_sigs` (`module::function` -> normalized
+    /// signature) to its summary row, for `by_function_sig.json` in the index.
+    #[arg(long)]
+    embed_function_sigs: bool,
+
+    /// Print the package ids exposing a function with this signature (as in
+    /// `function_sigs`, whitespace-insensitive), from `<--index-out-dir>/by_function_sig.json`.
+    #[arg(long, value_name = "SIG")]
+    query_function_sig: Option<String>,
+
     /// Build index artifacts from a summary JSONL (writes into --index-out-dir)
     #[arg(long)]
     index_from_summary_jsonl: Option<PathBuf>,
@@ -72,6 +315,10 @@ struct Args {
     #[arg(long, default_value = "/tmp/bytecode_move_model2_index")]
     index_out_dir: PathBuf,
 
+    /// Artifact format for `--index-from-summary-jsonl`.
+    #[arg(long, value_enum, default_value_t = IndexFormat::Json)]
+    index_format: IndexFormat,
+
     /// Verify RPC normalized module inventory matches local compiled module inventory.
     #[arg(long, value_name = "PATH")]
     verify_inventory_from_summary_jsonl: Option<PathBuf>,
@@ -84,9 +331,342 @@ struct Args {
     #[arg(long, value_name = "N")]
     verify_inventory_sample_size: Option<usize>,
 
+    /// Shuffle the ids with this seed before taking `--verify-inventory-sample-size`, for a
+    /// reproducible random sample instead of the lexicographically smallest ids.
+    #[arg(long, value_name = "N")]
+    verify_sample_seed: Option<u64>,
+
     /// Output directory for corpus-format results (detailed stats matching extractor1 schema).
     #[arg(long, value_name = "DIR")]
     corpus_out_dir: Option<PathBuf>,
+
+    /// Write a JSON index mapping each datatype defined in the local dataset
+    /// (`address::module::Name`) to the package ids that define it.
+    #[arg(long, value_name = "PATH")]
+    type_definition_index: Option<PathBuf>,
+
+    /// Write per-package and total counts of public function parameters passed by value,
+    /// by `&`, and by `&mut` across the local dataset (JSON).
+    #[arg(long, value_name = "PATH")]
+    param_convention_stats: Option<PathBuf>,
+
+    /// After a batch or verify run, re-run packages whose rows errored (not mismatched)
+    /// and merge the retry results over the originals.
+    #[arg(long, default_value_t = false)]
+    retry_failed_packages: bool,
+
+    /// Number of retry passes for `--retry-failed-packages`.
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    retry_passes: usize,
+
+    /// Write groups of local dataset packages whose module bytes are identical (JSON).
+    #[arg(long, value_name = "PATH")]
+    find_duplicate_packages: Option<PathBuf>,
+
+    /// Cap the per-function/per-struct `diffs` list of a verify row at N entries
+    /// (`diff_summary` counts stay complete).
+    #[arg(long, value_name = "N")]
+    max_diffs_per_package: Option<usize>,
+
+    /// Also write inventory verification rows as CSV.
+    #[arg(long, value_name = "PATH")]
+    verify_inventory_out_csv: Option<PathBuf>,
+
+    /// Also upsert inventory verification rows into the `verify_results` table of this
+    /// SQLite database (created if missing), keyed by `resolved_package_id`.
+    #[arg(long, value_name = "PATH")]
+    verify_out_sqlite: Option<PathBuf>,
+
+    /// Comma-separated CSV columns, in output order (default: all columns).
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',')]
+    output_fields: Vec<String>,
+
+    /// Omit the CSV header row and append to an existing CSV file.
+    #[arg(long, default_value_t = false)]
+    csv_no_header: bool,
+
+    /// Compare the dataset `metadata.json` `sourceDigest` against the on-chain package's
+    /// published source digest, flagging `source_digest_mismatch` (one extra RPC call).
+    #[arg(long, default_value_t = false)]
+    check_source_digest: bool,
+
+    /// Look up the package's `UpgradeCap` and record its policy (`compatible`, `additive`,
+    /// `dep_only`, `immutable`) as `upgrade_policy` (up to three extra RPC calls).
+    #[arg(long, default_value_t = false)]
+    fetch_upgrade_policy: bool,
+
+    /// Print each package's upgrade chain (original id -> ... -> latest) as a JSON line,
+    /// discovered from on-chain `UpgradeCap` usage (JSON-RPC only).
+    #[arg(long, default_value_t = false)]
+    print_upgrade_chain: bool,
+
+    /// JSON file mapping package id -> [module names]; verification of a listed package
+    /// is restricted to those modules on both sides. Unlisted packages verify in full.
+    #[arg(long, value_name = "PATH")]
+    modules_allowlist_file: Option<PathBuf>,
+
+    /// JSON file mapping package id -> {local module name: RPC module name}; local modules
+    /// are renamed before comparison (and before `--modules-allowlist-file`, whose names are
+    /// then the RPC ones).
+    #[arg(long, value_name = "PATH")]
+    module_rename_map: Option<PathBuf>,
+
+    /// JSON file mapping package id -> original package id, consulted before the dataset's
+    /// metadata.json when picking which modules belong to an upgraded package.
+    #[arg(long, value_name = "PATH", value_parser = parse_original_id_overrides)]
+    original_id_overrides: Option<BTreeMap<String, String>>,
+
+    /// After a verify run, write the sorted union of diff categories seen across rows (JSON).
+    #[arg(long, value_name = "PATH")]
+    emit_category_list: Option<PathBuf>,
+
+    /// After a verify run, write a matrix of how many packages exhibit each pair of diff
+    /// categories together (JSON).
+    #[arg(long, value_name = "PATH")]
+    category_correlation: Option<PathBuf>,
+
+    /// After a verify run, write totals across rows (JSON): ok and failing package counts,
+    /// the summed `diff_summary`, and the module names with diffs in the most packages.
+    #[arg(long, value_name = "PATH")]
+    verify_aggregate_out: Option<PathBuf>,
+
+    /// Exit non-zero after a verify run if any package has a breaking diff (a removed or
+    /// changed module, function, struct or enum; additions are non-breaking).
+    #[arg(long)]
+    fail_on_breaking: bool,
+
+    /// Exit non-zero after a verify run if any package is not `ok` (a diff, a missing
+    /// module, or an error).
+    #[arg(long)]
+    fail_on_diff: bool,
+
+    /// After a verify run, write a Markdown report: a summary table of all packages and a
+    /// section per failing package with its diff categories.
+    #[arg(long, value_name = "PATH")]
+    verify_report_md: Option<PathBuf>,
+
+    /// Report `module_address_mismatch` when a module's local self-address differs from the
+    /// RPC normalized module `address`. Off by default: upgraded packages legitimately differ.
+    #[arg(long, default_value_t = false)]
+    check_module_address: bool,
+
+    /// Print the total time spent in each RPC method to stderr at the end of the run.
+    #[arg(long, default_value_t = false)]
+    print_rpc_method_timings: bool,
+
+    /// Write RPC call counts (total and per method) and `--rpc-cache-dir` hits/misses as
+    /// JSON at the end of the run.
+    #[arg(long, value_name = "PATH")]
+    metrics_out: Option<PathBuf>,
+
+    /// Diff the public interfaces of two package ids (e.g. before and after an upgrade)
+    /// and print added/removed/changed modules, functions and structs as JSON.
+    /// With `--baseline-inventory`, pass only NEW_ID.
+    #[arg(long, num_args = 1..=2, value_names = ["OLD_ID", "NEW_ID"])]
+    diff_packages: Option<Vec<String>>,
+
+    /// Use a stored inventory (as written by `--emit-inventory-json`) as the old side of
+    /// `--diff-packages` instead of loading OLD_ID.
+    #[arg(long, value_name = "PATH", requires = "diff_packages")]
+    baseline_inventory: Option<PathBuf>,
+
+    /// Diff two normalized-modules JSON files (A = self, B = other) and print the result.
+    /// Addresses are compared only with `--check-module-address`; no RPC client is built.
+    #[arg(long, num_args = 2, value_names = ["A", "B"])]
+    compare_normalized: Option<Vec<PathBuf>>,
+
+    /// Diff the live RPC interface of each package against `<DIR>/<package_id>.json`, an
+    /// expected inventory (as written by `--emit-inventory-json`). Exits non-zero on drift.
+    #[arg(long, value_name = "DIR")]
+    baseline_dir: Option<PathBuf>,
+
+    /// Triangulate each package's interface across two `sui-packages` checkouts (A =
+    /// current, B = older snapshot) and live RPC, classifying every differing item.
+    #[arg(long, num_args = 2, value_names = ["A", "B"])]
+    three_way: Option<Vec<PathBuf>>,
+
+    /// Write compact verify rows with only `resolved_package_id`, `ok`, `n_diff_modules`
+    /// and `n_missing` (modules missing on either side) instead of the full detail.
+    #[arg(long, default_value_t = false)]
+    modules_summary_only: bool,
+
+    /// `text` additionally prints a per-module breakdown of every differing package to
+    /// stdout, with decoded local vs RPC signatures. The JSONL output is written either way.
+    #[arg(long, value_enum, default_value_t = VerifyReportFormat::Jsonl)]
+    verify_report_format: VerifyReportFormat,
+
+    /// Snake-case struct field names on both sides before comparing (names are normally
+    /// authoritative, so this is off by default). Renamed fields are listed in the row.
+    #[arg(long, default_value_t = false)]
+    normalize_field_names: bool,
+
+    /// Before comparing, rewrite `Struct.address` values inside type signatures that name
+    /// the verified package itself (its id, its original id from metadata/overrides, or any
+    /// of its modules' self addresses on either side) to `0xSELF`. Other addresses,
+    /// including dependencies and the framework, are left as-is. Comparison only.
+    #[arg(long, default_value_t = false)]
+    ignore_self_address: bool,
+
+    /// Compare struct fields as a set (sorted by name) instead of in declaration order.
+    /// By default a reordering is reported as `struct_field_order_mismatch`.
+    #[arg(long, default_value_t = false)]
+    sort_fields: bool,
+
+    /// With `--index-from-summary-jsonl`, load the existing artifacts in `--index-out-dir`
+    /// and index only the summary rows appended since (`meta.rows + meta.skipped` lines
+    /// are skipped).
+    #[arg(long, default_value_t = false)]
+    index_append: bool,
+
+    /// With `--index-from-summary-jsonl`, log and skip unparseable summary lines (e.g. a
+    /// row cut off by a killed run) instead of failing; JSON indexes count them in
+    /// `meta.skipped`.
+    #[arg(long, default_value_t = false)]
+    tolerate_bad_lines: bool,
+
+    /// Number of packages processed concurrently in batch and inventory verify mode; also
+    /// the number of threads deserializing local `.mv` files.
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+
+    /// Write `<DIR>/<package_id>.json` listing each public generic function and generic
+    /// struct with the ability constraints of its type parameters.
+    #[arg(long, value_name = "DIR")]
+    export_generic_bounds: Option<PathBuf>,
+
+    /// Write the root package's full interface inventory (functions, structs) as
+    /// canonicalized JSON. Requires a single package id.
+    #[arg(long, value_name = "PATH")]
+    emit_inventory_json: Option<PathBuf>,
+
+    /// Compare the root package against a stored inventory (as written by
+    /// `--emit-inventory-json`) and print a JSON report of public functions and structs
+    /// that were removed or changed. Exits non-zero on any regression. Requires a single
+    /// package id.
+    #[arg(long, value_name = "BASELINE.json")]
+    interface_regression: Option<PathBuf>,
+
+    /// Write every public function of the root package, one per line, as
+    /// `module::function(param_types) -> (return_types)` with types in Sui type-tag form
+    /// (`0x2::coin::Coin<0x...::usdc::USDC>`). Requires a single package id.
+    #[arg(long, value_name = "PATH")]
+    emit_signatures: Option<PathBuf>,
+
+    /// Write `{package_id: {module: sha256}}` interface fingerprints of each root package
+    /// (JSON), for cheap change detection across runs.
+    #[arg(long, value_name = "PATH")]
+    emit_fingerprints: Option<PathBuf>,
+
+    /// Write `<DIR>/<module>.move` signature stubs (structs, enums, `native` functions)
+    /// for the root package. Requires a single package id.
+    #[arg(long, value_name = "DIR")]
+    emit_move_stub: Option<PathBuf>,
+
+    /// Write `<DIR>/<module>.ts` with an exported TypeScript interface per struct of the
+    /// root package. Requires a single package id.
+    #[arg(long, value_name = "DIR")]
+    emit_ts: Option<PathBuf>,
+
+    /// Write the root package's transitive dependency graph (from linkage tables) as a
+    /// Graphviz DOT file, as walked by the extraction itself (so `--max-dep-depth` applies).
+    /// Requires a single package id.
+    #[arg(long, value_name = "PATH")]
+    emit_dep_graph: Option<PathBuf>,
+
+    /// Write the single-package result (module names, stackless summary or error, resolved
+    /// artifact dirs) as pretty-printed JSON. Requires a single package id.
+    #[arg(long, value_name = "PATH")]
+    single_out: Option<PathBuf>,
+
+    /// For each package whose extraction errors or panics, copy its `.mv` files and
+    /// `metadata.json` into `<DIR>/<package_id>/` as a reproduction bundle.
+    #[arg(long, value_name = "DIR")]
+    dump_failing_bytecode: Option<PathBuf>,
+
+    /// Retries for transient RPC failures (connection resets, timeouts, 429/5xx).
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    rpc_max_retries: usize,
+
+    /// Base delay for exponential RPC retry backoff, in milliseconds.
+    #[arg(long, value_name = "MS", default_value_t = 500)]
+    rpc_base_delay_ms: u64,
+
+    /// Timeout for each RPC attempt, in seconds. Timed-out attempts are retried.
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    rpc_timeout_secs: u64,
+
+    /// Overall timeout for fetching one package and its RPC dependencies, in seconds.
+    #[arg(long, value_name = "SECS")]
+    package_timeout_secs: Option<u64>,
+
+    /// Maximum number of RPC requests in flight at once, across all `--jobs` (default:
+    /// unlimited).
+    #[arg(long, value_name = "N")]
+    rpc_concurrency: Option<usize>,
+
+    /// Follow linkage tables at most N hops from the package being loaded (0 = the
+    /// package's own modules only). Unbounded by default.
+    #[arg(long, value_name = "N")]
+    max_dep_depth: Option<usize>,
+
+    /// Fail packages whose linkage graph contains a cycle instead of reporting it in
+    /// `dependency_cycle`.
+    #[arg(long)]
+    fail_on_dep_cycle: bool,
+
+    /// Cache fetched package modules and linkage tables under this directory, keyed by
+    /// object id and validated against the current object digest.
+    #[arg(long, value_name = "DIR")]
+    rpc_cache_dir: Option<PathBuf>,
+
+    /// Bypass `--rpc-cache-dir` entirely (no reads, no writes).
+    #[arg(long, default_value_t = false)]
+    no_cache: bool,
+
+    /// Skip (batch and verify) any package whose local `.mv` files total more than N bytes;
+    /// the row records the skip reason in `skipped`.
+    #[arg(long, value_name = "N")]
+    skip_if_module_bytes_over: Option<u64>,
+
+    /// Periodically overwrite this file with run progress
+    /// (`processed`, `total`, `ok`, `failed`, `current_package`, `elapsed_secs`) as JSON.
+    #[arg(long, value_name = "PATH")]
+    checkpoint_file: Option<PathBuf>,
+}
+
+/// Read an `--original-id-overrides` file, keyed by `normalize_package_id`.
+fn parse_original_id_overrides(path: &str) -> Result<BTreeMap<String, String>> {
+    read_package_keyed_json(Path::new(path))
+}
+
+impl Args {
+    fn inventory_options(&self) -> InventoryOptions {
+        InventoryOptions {
+            function_scope: self.function_scope,
+            module_filter: self.module_filter.clone(),
+            max_type_depth: self.max_type_depth,
+        }
+    }
+
+    /// `--dataset-network`, else the `--network` preset's, else mainnet.
+    fn resolved_dataset_network(&self) -> MvrNetwork {
+        self.dataset_network
+            .or_else(|| self.network.and_then(Network::dataset_network))
+            .unwrap_or(MvrNetwork::Mainnet)
+    }
+
+    fn dataset_options(&self) -> DatasetOptions {
+        DatasetOptions {
+            root: self
+                .dataset_dir
+                .clone()
+                .unwrap_or_else(default_dataset_root),
+            network: self.resolved_dataset_network(),
+            original_id_overrides: self.original_id_overrides.clone().unwrap_or_default(),
+            skip_bad_modules: self.skip_bad_modules,
+            strict: self.strict,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -96,8 +676,22 @@ struct LocalBytecodeModuleList {
     resolved_artifact_dir: String,
     resolved_bytecode_modules_dir: String,
     module_names: Vec<String>,
+    /// On-chain package version, from the dataset's `bcs.json` or RPC; `None` if unknown.
+    version: Option<u64>,
+    /// `.mv` files skipped under `--skip-bad-modules`.
+    skipped_modules: usize,
     stackless_summary: Option<StacklessSummary>,
-    stackless_error: Option<String>,
+    stackless_error: Option<StacklessError>,
+    /// Root modules that still fail translation on their own; only filled by
+    /// `--isolate-stackless-failures` after a whole-package failure.
+    stackless_failed_modules: Vec<String>,
+    /// `module::function` -> `FunctionInv::signature` of the root modules; only built for
+    /// `--embed-function-sigs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function_sigs: Option<BTreeMap<String, String>>,
+    /// Dependency graph walked while loading, for `--emit-dep-graph`.
+    #[serde(skip)]
+    dep_graph: DepGraph,
 }
 
 #[derive(Debug, Serialize)]
@@ -107,22 +701,49 @@ struct StacklessSummary {
     functions: usize,
     structs: usize,
     not_implemented_instructions: usize,
+    /// Not-implemented instructions grouped by opcode name, counted from the translated AST.
+    not_implemented_by_opcode: BTreeMap<String, usize>,
     stack_underflow_pops: usize,
 }
 
+/// Opcode name of a `NotImplemented` payload, e.g. `VecPack` from `VecPack(3, 2)`.
+fn not_implemented_opcode(payload: &str) -> String {
+    payload
+        .trim()
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()
+        .filter(|s| !s.is_empty())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// The stackless AST carries no datatype definitions, so `structs` is counted by the caller
+/// from the compiled modules that were translated.
 fn count_stackless(
     bytecode: &move_stackless_bytecode_2::ast::StacklessBytecode,
+    structs: usize,
 ) -> StacklessSummary {
     let mut packages = 0usize;
     let mut modules = 0usize;
     let mut functions = 0usize;
-    let structs = 0usize;
+    let mut not_implemented_by_opcode = BTreeMap::new();
 
     for pkg in &bytecode.packages {
         packages += 1;
         for (_mname, m) in &pkg.modules {
             modules += 1;
             functions += m.functions.len();
+            for f in m.functions.values() {
+                for block in f.basic_blocks.values() {
+                    for instr in &block.instructions {
+                        if let Instruction::NotImplemented(payload) = instr {
+                            *not_implemented_by_opcode
+                                .entry(not_implemented_opcode(payload))
+                                .or_default() += 1;
+                        }
+                    }
+                }
+            }
         }
     }
 
@@ -132,328 +753,110 @@ fn count_stackless(
         functions,
         structs,
         not_implemented_instructions: 0,
+        not_implemented_by_opcode,
         stack_underflow_pops: 0,
     }
 }
 
+/// Struct definitions across `modules`, reported as `StacklessSummary::structs`.
+fn struct_count(modules: &[Arc<CompiledModule>]) -> usize {
+    modules.iter().map(|m| m.struct_defs().len()).sum()
+}
+
 fn count_stackless_with_stats(
     bytecode: &move_stackless_bytecode_2::ast::StacklessBytecode,
     stats: move_stackless_bytecode_2::translate::TranslationStats,
+    structs: usize,
 ) -> StacklessSummary {
-    let mut s = count_stackless(bytecode);
+    let mut s = count_stackless(bytecode, structs);
     s.not_implemented_instructions = stats.not_implemented_instructions;
     s.stack_underflow_pops = stats.stack_underflow_pops;
     s
 }
 
-fn load_compiled_modules_from_bytecode_modules_dir(
-    bytecode_modules_dir: &Path,
-) -> Result<Vec<CompiledModule>> {
-    let mut modules = Vec::new();
-    for entry in fs::read_dir(bytecode_modules_dir)
-        .with_context(|| format!("read_dir {}", bytecode_modules_dir.display()))?
-    {
-        let entry = entry?;
-        let path = entry.path();
-        if path.extension().and_then(|s| s.to_str()) != Some("mv") {
+/// One id per line; blank lines and `#` comments are skipped.
+fn insert_id_lines(ids: &mut BTreeSet<String>, text: &str) {
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
             continue;
         }
-        let bytes = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
-        let module = CompiledModule::deserialize_with_defaults(&bytes)
-            .with_context(|| format!("deserialize {}", path.display()))?;
-        modules.push(module);
+        ids.insert(line.to_string());
     }
-    Ok(modules)
 }
 
-fn parse_linkage_deps_from_artifact_dir(artifact_dir: &Path) -> Result<Vec<String>> {
-    let bcs_path = artifact_dir.join("bcs.json");
-    let bcs_text =
-        fs::read_to_string(&bcs_path).with_context(|| format!("read {}", bcs_path.display()))?;
-    let bcs_value: Value = serde_json::from_str(&bcs_text)
-        .with_context(|| format!("parse json {}", bcs_path.display()))?;
+async fn collect_package_ids(args: &Args) -> Result<Vec<String>> {
+    let mut ids = BTreeSet::<String>::new();
 
-    let mut deps = Vec::new();
-    if let Some(linkage) = bcs_value.get("linkageTable").and_then(Value::as_object) {
-        for (dep_id, _info) in linkage {
-            deps.push(dep_id.to_string());
+    for id in &args.package_id {
+        let trimmed = id.trim();
+        if !trimmed.is_empty() {
+            ids.insert(trimmed.to_string());
         }
     }
-    Ok(deps)
-}
-
-fn try_load_local_modules_for_package(package_id: &str) -> Result<Option<Vec<CompiledModule>>> {
-    let artifact_dir = match sui_packages_artifact_dir_for_package_id(package_id)
-        .and_then(|p| p.canonicalize().map_err(|e| anyhow!(e)))
-    {
-        Ok(p) => p,
-        Err(_) => return Ok(None),
-    };
-    let bytecode_dir = artifact_dir.join("bytecode_modules");
-    let mods = load_compiled_modules_from_bytecode_modules_dir(&bytecode_dir)
-        .with_context(|| format!("load local modules {}", package_id))?;
-    Ok(Some(mods))
-}
-
-async fn fetch_compiled_modules_via_rpc(
-    client: Arc<sui_sdk::SuiClient>,
-    package_id: ObjectID,
-) -> Result<Vec<CompiledModule>> {
-    // Fetch raw package object and extract module bytes.
-    let resp = client
-        .read_api()
-        .get_object_with_options(
-            package_id,
-            sui_sdk::rpc_types::SuiObjectDataOptions::new().with_bcs(),
-        )
-        .await
-        .with_context(|| format!("fetch package object {}", package_id))?;
-
-    let data = resp
-        .data
-        .ok_or_else(|| anyhow!("missing object data for {}", package_id))?;
-    let bcs = data
-        .bcs
-        .ok_or_else(|| anyhow!("missing bcs for {}", package_id))?;
-
-    let pkg = match bcs {
-        sui_sdk::rpc_types::SuiRawData::Package(pkg) => pkg,
-        _ => return Err(anyhow!("object {} is not a package", package_id)),
-    };
 
-    let mut modules = Vec::new();
-    for (name, bytes) in pkg.module_map {
-        let module = CompiledModule::deserialize_with_defaults(&bytes)
-            .with_context(|| format!("deserialize rpc module {}::{}", package_id, name))?;
-        modules.push(module);
+    if let Some(path) = args.package_ids_file.as_ref() {
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        insert_id_lines(&mut ids, &text);
     }
-    Ok(modules)
-}
-
-async fn fetch_dependency_package_ids_via_rpc(
-    client: Arc<sui_sdk::SuiClient>,
-    package_id: ObjectID,
-) -> Result<Vec<String>> {
-    let resp = client
-        .read_api()
-        .get_object_with_options(
-            package_id,
-            sui_sdk::rpc_types::SuiObjectDataOptions::new().with_bcs(),
-        )
-        .await
-        .with_context(|| format!("fetch package object {}", package_id))?;
-
-    let data = resp
-        .data
-        .ok_or_else(|| anyhow!("missing object data for {}", package_id))?;
-    let bcs = data
-        .bcs
-        .ok_or_else(|| anyhow!("missing bcs for {}", package_id))?;
-
-    let pkg = match bcs {
-        sui_sdk::rpc_types::SuiRawData::Package(pkg) => pkg,
-        _ => return Err(anyhow!("object {} is not a package", package_id)),
-    };
 
-    let deps = pkg
-        .linkage_table
-        .into_iter()
-        .map(|(id, _linkage)| id.to_string())
-        .collect::<Vec<_>>();
-    Ok(deps)
-}
-
-async fn load_compiled_modules_with_rpc_deps(
-    client: Arc<sui_sdk::SuiClient>,
-    root_package_id: &str,
-) -> Result<Vec<CompiledModule>> {
-    let mut seen: BTreeSet<String> = BTreeSet::new();
-    let mut queue: Vec<String> = vec![root_package_id.to_string()];
-    let mut all_modules: Vec<CompiledModule> = Vec::new();
-
-    // Ensure we always load the root package modules, even if local dataset lookup fails.
-    // This prevents later inventory verification from operating on deps-only results.
-    if let Some(mut root_local) = try_load_local_modules_for_package(root_package_id)? {
-        all_modules.append(&mut root_local);
-    } else {
-        let root_oid = object_id_from_hex_str(root_package_id)
-            .map_err(|e| anyhow!("invalid root package id {}: {}", root_package_id, e))?;
-        let mut root_rpc = fetch_compiled_modules_via_rpc(Arc::clone(&client), root_oid).await?;
-        all_modules.append(&mut root_rpc);
+    if args.package_ids_stdin {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .context("read package ids from stdin")?;
+        insert_id_lines(&mut ids, &text);
     }
 
-    while let Some(pid) = queue.pop() {
-        if !seen.insert(pid.clone()) {
-            continue;
-        }
-
-        // Root already loaded above.
-        if pid == root_package_id {
-            continue;
-        }
+    if let Some(path) = args.mvr_catalog.as_ref() {
+        let catalog_text =
+            fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        let catalog: Value = serde_json::from_str(&catalog_text)
+            .with_context(|| format!("parse {}", path.display()))?;
+        let Some(names) = catalog.get("names").and_then(Value::as_array) else {
+            return Err(anyhow!("mvr catalog missing 'names' array"));
+        };
 
-        if let Some(mut local_mods) = try_load_local_modules_for_package(&pid)? {
-            all_modules.append(&mut local_mods);
+        let field = args
+            .mvr_network
+            .unwrap_or_else(|| args.resolved_dataset_network())
+            .mvr_package_info_field();
 
-            // Only local artifacts have bcs.json linkage info.
-            let artifact_dir = sui_packages_artifact_dir_for_package_id(&pid)?
-                .canonicalize()
-                .with_context(|| format!("canonicalize artifact dir for {}", pid))?;
-            for dep in parse_linkage_deps_from_artifact_dir(&artifact_dir)? {
-                if !seen.contains(&dep) {
-                    queue.push(dep);
+        // Entries without an id for this network (common for devnet) are skipped.
+        let mut found = 0usize;
+        for item in names {
+            if let Some(id) = item.get(field).and_then(Value::as_str) {
+                let trimmed = id.trim();
+                if !trimmed.is_empty() {
+                    ids.insert(trimmed.to_string());
+                    found += 1;
                 }
             }
-            continue;
         }
-
-        // Not in local dataset; fetch from RPC.
-        // Dependency IDs may be 0x-prefixed 64-hex; ObjectID::from_str expects the same.
-        let oid = object_id_from_hex_str(&pid)
-            .map_err(|e| anyhow!("invalid dep package id {}: {}", pid, e))?;
-
-        // Discover additional deps from the on-chain linkage table.
-        if let Ok(deps) = fetch_dependency_package_ids_via_rpc(Arc::clone(&client), oid).await {
-            for dep in deps {
-                if !seen.contains(&dep) {
-                    queue.push(dep);
-                }
-            }
+        if found == 0 {
+            tracing::warn!(catalog = %path.display(), field, "mvr catalog has no entries");
         }
-
-        let mut rpc_mods = fetch_compiled_modules_via_rpc(Arc::clone(&client), oid).await?;
-        all_modules.append(&mut rpc_mods);
     }
 
-    Ok(all_modules)
-}
-
-fn extract_module_names_from_bytecode_modules_dir(
-    bytecode_modules_dir: &Path,
-) -> Result<Vec<String>> {
-    let mut names = Vec::new();
-    for entry in fs::read_dir(bytecode_modules_dir)
-        .with_context(|| format!("read_dir {}", bytecode_modules_dir.display()))?
-    {
-        let entry = entry?;
-        let path = entry.path();
-        if path.extension().and_then(|s| s.to_str()) != Some("mv") {
-            continue;
+    if !args.mvr_name.is_empty() {
+        let network = args
+            .mvr_network
+            .unwrap_or_else(|| args.resolved_dataset_network());
+        let http = reqwest::Client::new();
+        for name in &args.mvr_name {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            ids.insert(resolve_mvr_name(&http, name, network).await?);
         }
-        let bytes = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
-        let module = CompiledModule::deserialize_with_defaults(&bytes)
-            .with_context(|| format!("deserialize {}", path.display()))?;
-        names.push(module.self_id().name().to_string());
     }
-    names.sort();
-    names.dedup();
-    Ok(names)
-}
 
-fn canonicalize_json_value(value: &mut Value) {
-    match value {
-        Value::Object(map) => {
-            let old_map = std::mem::take(map);
-            let mut entries: Vec<(String, Value)> = old_map.into_iter().collect();
-            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
-
-            for (_, v) in entries.iter_mut() {
-                canonicalize_json_value(v);
-            }
-
-            for (k, v) in entries {
-                map.insert(k, v);
-            }
-        }
-        Value::Array(values) => {
-            for v in values.iter_mut() {
-                canonicalize_json_value(v);
-            }
-        }
-        _ => {}
+    let mut ids: Vec<String> = ids.into_iter().collect();
+    if let Some(max) = args.max_packages {
+        ids.truncate(max);
     }
-}
-
-fn collect_package_ids(args: &Args) -> Result<Vec<String>> {
-    let mut ids = BTreeSet::<String>::new();
-
-    for id in &args.package_id {
-        let trimmed = id.trim();
-        if !trimmed.is_empty() {
-            ids.insert(trimmed.to_string());
-        }
-    }
-
-    if let Some(path) = args.package_ids_file.as_ref() {
-        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
-        for line in text.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-            ids.insert(line.to_string());
-        }
-    }
-
-    if let Some(path) = args.mvr_catalog.as_ref() {
-        let catalog_text =
-            fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
-        let catalog: Value = serde_json::from_str(&catalog_text)
-            .with_context(|| format!("parse {}", path.display()))?;
-        let Some(names) = catalog.get("names").and_then(Value::as_array) else {
-            return Err(anyhow!("mvr catalog missing 'names' array"));
-        };
-
-        let field = match args.mvr_network {
-            MvrNetwork::Mainnet => "mainnet_package_info_id",
-            MvrNetwork::Testnet => "testnet_package_info_id",
-        };
-
-        for item in names {
-            if let Some(id) = item.get(field).and_then(Value::as_str) {
-                let trimmed = id.trim();
-                if !trimmed.is_empty() {
-                    ids.insert(trimmed.to_string());
-                }
-            }
-        }
-    }
-
-    let mut ids: Vec<String> = ids.into_iter().collect();
-    if let Some(max) = args.max_packages {
-        ids.truncate(max);
-    }
-    Ok(ids)
-}
-
-#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
-struct TypeSig(String);
-
-#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
-struct FunctionInv {
-    visibility: Option<String>,
-    is_entry: Option<bool>,
-    type_params: Option<usize>,
-    params: Vec<TypeSig>,
-    returns: Vec<TypeSig>,
-}
-
-#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
-struct StructInv {
-    abilities: Vec<String>,
-    type_params: Option<usize>,
-    fields: Vec<(String, TypeSig)>,
-}
-
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
-struct ModuleInventory {
-    functions: BTreeMap<String, FunctionInv>,
-    structs: BTreeMap<String, StructInv>,
-}
-
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
-struct PackageInventory {
-    modules: BTreeMap<String, ModuleInventory>,
+    Ok(ids)
 }
 
 /// Local bytecode statistics (from .mv files)
@@ -471,6 +874,8 @@ struct LocalStats {
     friend_entry_functions: usize,
     private_entry_functions: usize,
     key_structs: usize,
+    /// `.mv` files skipped under `--skip-bad-modules` (set by the caller).
+    skipped_modules: usize,
 }
 
 /// RPC normalized module statistics
@@ -519,211 +924,270 @@ struct CorpusReportRow {
     interface_compare: InterfaceCompare,
     interface_compare_sample: Option<Vec<Value>>,
 
-    error: Option<String>,
+    error: Option<VerifyError>,
+}
+
+/// Why stackless translation of a package failed, serialized like `VerifyError`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+enum StacklessError {
+    /// `from_compiled_modules` returned an error.
+    Error(String),
+    /// Translation panicked.
+    Panic(String),
+}
+
+impl std::fmt::Display for StacklessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StacklessError::Error(m) => write!(f, "error: {}", m),
+            StacklessError::Panic(m) => write!(f, "panic: {}", m),
+        }
+    }
+}
+
+/// A summary row's `stackless_error` as `kind: message`; rows written before it was typed
+/// carry that string already.
+fn stackless_error_text(row: &Value) -> Option<String> {
+    match row.get("stackless_error")? {
+        Value::String(s) => Some(s.clone()),
+        Value::Object(e) => {
+            let kind = e.get("kind").and_then(Value::as_str).unwrap_or("unknown");
+            match e.get("message").and_then(Value::as_str) {
+                Some(message) => Some(format!("{}: {}", kind, message)),
+                None => Some(kind.to_string()),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Why a package could not be verified, serialized as `{"kind": ..., "message": ...}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+enum VerifyError {
+    InvalidObjectId(String),
+    RpcNormalizedModules(String),
+    RpcInventoryParse(String),
+    RpcReturnedNoModules,
+    RpcSourceDigest(String),
+    LocalModulesLoad(String),
+    LocalPackageNotFound(String),
+    RpcTimeout(String),
+    DependencyCycle(String),
+}
+
+impl VerifyError {
+    /// Wrap an RPC-side failure as `kind`, unless it was a timeout, which gets its own kind.
+    fn from_rpc(e: &anyhow::Error, kind: fn(String) -> VerifyError) -> VerifyError {
+        if is_rpc_timeout(e) {
+            VerifyError::RpcTimeout(format!("{e:#}"))
+        } else {
+            kind(format!("{e:#}"))
+        }
+    }
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (kind, message) = match self {
+            VerifyError::InvalidObjectId(m) => ("invalid_object_id", m.as_str()),
+            VerifyError::RpcNormalizedModules(m) => ("rpc_normalized_modules", m.as_str()),
+            VerifyError::RpcInventoryParse(m) => ("rpc_inventory_parse", m.as_str()),
+            VerifyError::RpcReturnedNoModules => ("rpc_returned_no_modules", ""),
+            VerifyError::RpcSourceDigest(m) => ("rpc_source_digest", m.as_str()),
+            VerifyError::LocalModulesLoad(m) => ("local_modules_load", m.as_str()),
+            VerifyError::LocalPackageNotFound(m) => ("local_package_not_found", m.as_str()),
+            VerifyError::RpcTimeout(m) => ("rpc_timeout", m.as_str()),
+            VerifyError::DependencyCycle(m) => ("dependency_cycle", m.as_str()),
+        };
+        if message.is_empty() {
+            write!(f, "{}", kind)
+        } else {
+            write!(f, "{}: {}", kind, message)
+        }
+    }
 }
 
 /// Legacy simple output format (for backwards compatibility)
 #[derive(Debug, serde::Serialize)]
 struct InventoryVerifyRow {
     resolved_package_id: String,
+    /// On-chain version of the package's local-side modules; `None` if unknown.
+    version: Option<u64>,
     ok: bool,
-    error: Option<String>,
+    error: Option<VerifyError>,
     modules_missing_local: Vec<String>,
     modules_missing_rpc: Vec<String>,
     modules_with_diffs: Vec<String>,
     diff_summary: BTreeMap<String, usize>,
+    /// Severity of each `diff_summary` category; breaking if any of its entries is.
+    diff_severity: BTreeMap<String, Severity>,
+    /// A module missing on RPC or any breaking category (`PackageInventoryDiff::is_breaking`).
+    breaking: bool,
+    diffs: Vec<DiffEntry>,
+    diffs_truncated: bool,
+    source_digest_mismatch: bool,
+    normalized_field_names: Vec<String>,
+    upgrade_policy: Option<String>,
+    /// Field-level detail for each mismatched struct, keyed by `module::Struct`.
+    struct_diffs: BTreeMap<String, StructDiff>,
+    /// Why the package was not verified (e.g. `--skip-if-module-bytes-over`).
+    skipped: Option<String>,
+    /// Dependency ids not loaded because of `--max-dep-depth`; non-empty means the local
+    /// side was built from a partial dependency graph.
+    deps_truncated: Vec<String>,
+    /// Linkage cycles found while loading the local side, as package id paths.
+    dependency_cycle: Vec<Vec<String>>,
+    /// Local `.mv` files skipped as undeserializable under `--skip-bad-modules`.
+    skipped_modules: usize,
+    /// `--verify-sample-seed` the package was sampled with.
+    sample_seed: Option<u64>,
+    /// `--verify-report-format text` lines, keyed by module.
+    #[serde(skip)]
+    text_report: BTreeMap<String, Vec<String>>,
 }
 
-fn stable_json(v: &Value) -> String {
-    let mut v = v.clone();
-    canonicalize_json_value(&mut v);
-    serde_json::to_string(&v).expect("serialize")
+/// `--modules-summary-only` form of an `InventoryVerifyRow`.
+#[derive(Debug, Serialize)]
+struct InventoryVerifySummaryRow<'a> {
+    resolved_package_id: &'a str,
+    ok: bool,
+    n_diff_modules: usize,
+    n_missing: usize,
 }
 
-fn type_sig_from_value(v: &Value) -> TypeSig {
-    TypeSig(stable_json(v))
+impl<'a> From<&'a InventoryVerifyRow> for InventoryVerifySummaryRow<'a> {
+    fn from(row: &'a InventoryVerifyRow) -> Self {
+        Self {
+            resolved_package_id: &row.resolved_package_id,
+            ok: row.ok,
+            n_diff_modules: row.modules_with_diffs.len(),
+            n_missing: row.modules_missing_local.len() + row.modules_missing_rpc.len(),
+        }
+    }
 }
 
-fn stable_debug<T: std::fmt::Debug>(v: &T) -> String {
-    format!("{v:?}")
+/// Per-package verification settings derived from the CLI.
+#[derive(Debug, Clone, Default)]
+struct VerifyOptions {
+    max_diffs_per_package: Option<usize>,
+    check_source_digest: bool,
+    fetch_upgrade_policy: bool,
+    check_module_address: bool,
+    normalize_field_names: bool,
+    sort_fields: bool,
+    ignore_self_address: bool,
+    skip_if_module_bytes_over: Option<u64>,
+    text_report: bool,
+    /// Keyed by `normalize_package_id`.
+    modules_allowlist: BTreeMap<String, BTreeSet<String>>,
+    /// Keyed by `normalize_package_id`; local module name -> RPC module name.
+    module_renames: BTreeMap<String, BTreeMap<String, String>>,
+    inventory: InventoryOptions,
+    dataset: DatasetOptions,
 }
 
-/// Convert SignatureToken to RPC-compatible JSON format.
-/// RPC uses PascalCase primitive types, camelCase keys, and short 0x addresses.
-fn type_sig_from_token(module: &CompiledModule, token: &SignatureToken) -> TypeSig {
-    fn to_rpc_json(module: &CompiledModule, token: &SignatureToken) -> Value {
-        match token {
-            SignatureToken::Bool => Value::String("Bool".to_string()),
-            SignatureToken::U8 => Value::String("U8".to_string()),
-            SignatureToken::U16 => Value::String("U16".to_string()),
-            SignatureToken::U32 => Value::String("U32".to_string()),
-            SignatureToken::U64 => Value::String("U64".to_string()),
-            SignatureToken::U128 => Value::String("U128".to_string()),
-            SignatureToken::U256 => Value::String("U256".to_string()),
-            SignatureToken::Address => Value::String("Address".to_string()),
-            SignatureToken::Signer => Value::String("Signer".to_string()),
-            SignatureToken::Vector(inner) => {
-                json!({"Vector": to_rpc_json(module, inner)})
-            }
-            SignatureToken::Datatype(idx) => {
-                let handle = module.datatype_handle_at(*idx);
-                let mod_handle = module.module_handle_at(handle.module);
-                let addr = module
-                    .address_identifier_at(mod_handle.address)
-                    .to_hex_literal();
-                let mod_name = module.identifier_at(mod_handle.name).as_str();
-                let name = module.identifier_at(handle.name).as_str();
-                json!({
-                    "Struct": {
-                        "address": addr,
-                        "module": mod_name,
-                        "name": name,
-                        "typeArguments": []
-                    }
-                })
-            }
-            SignatureToken::DatatypeInstantiation(inst) => {
-                let (idx, type_args) = inst.as_ref();
-                let handle = module.datatype_handle_at(*idx);
-                let mod_handle = module.module_handle_at(handle.module);
-                let addr = module
-                    .address_identifier_at(mod_handle.address)
-                    .to_hex_literal();
-                let mod_name = module.identifier_at(mod_handle.name).as_str();
-                let name = module.identifier_at(handle.name).as_str();
-                let args: Vec<Value> = type_args.iter().map(|t| to_rpc_json(module, t)).collect();
-                json!({
-                    "Struct": {
-                        "address": addr,
-                        "module": mod_name,
-                        "name": name,
-                        "typeArguments": args
-                    }
-                })
-            }
-            SignatureToken::Reference(inner) => {
-                json!({"Reference": to_rpc_json(module, inner)})
-            }
-            SignatureToken::MutableReference(inner) => {
-                json!({"MutableReference": to_rpc_json(module, inner)})
-            }
-            SignatureToken::TypeParameter(idx) => {
-                json!({"TypeParameter": *idx})
-            }
-        }
+impl VerifyOptions {
+    fn from_args(args: &Args) -> Result<Self> {
+        let modules_allowlist = match args.modules_allowlist_file.as_ref() {
+            Some(path) => read_package_keyed_json::<BTreeSet<String>>(path)?,
+            None => BTreeMap::new(),
+        };
+        let module_renames = match args.module_rename_map.as_ref() {
+            Some(path) => read_package_keyed_json::<BTreeMap<String, String>>(path)?,
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            max_diffs_per_package: args.max_diffs_per_package,
+            check_source_digest: args.check_source_digest,
+            fetch_upgrade_policy: args.fetch_upgrade_policy,
+            check_module_address: args.check_module_address,
+            normalize_field_names: args.normalize_field_names,
+            sort_fields: args.sort_fields,
+            ignore_self_address: args.ignore_self_address,
+            skip_if_module_bytes_over: args.skip_if_module_bytes_over,
+            text_report: args.verify_report_format == VerifyReportFormat::Text,
+            modules_allowlist,
+            module_renames,
+            inventory: args.inventory_options(),
+            dataset: args.dataset_options(),
+        })
     }
-    TypeSig(stable_json(&to_rpc_json(module, token)))
 }
 
-fn abilities_to_vec(abilities: &AbilitySet) -> Vec<String> {
-    let mut out = Vec::new();
-    if abilities.has_copy() {
-        out.push("copy".to_string());
-    }
-    if abilities.has_drop() {
-        out.push("drop".to_string());
-    }
-    if abilities.has_key() {
-        out.push("key".to_string());
-    }
-    if abilities.has_store() {
-        out.push("store".to_string());
-    }
-    out.sort();
-    out
+#[derive(Debug, Serialize)]
+struct DatatypeTypeParamBounds {
+    constraints: Vec<String>,
+    is_phantom: bool,
 }
 
-fn visibility_to_string(v: Visibility) -> String {
-    stable_debug(&v)
+/// Type parameter constraints of a package's generic public API, keyed `module::name`.
+#[derive(Debug, Serialize)]
+struct GenericBoundsExport {
+    package_id: String,
+    functions: BTreeMap<String, Vec<Vec<String>>>,
+    structs: BTreeMap<String, Vec<DatatypeTypeParamBounds>>,
 }
 
-fn module_inventory_from_compiled_module(m: &CompiledModule) -> ModuleInventory {
+fn generic_bounds_from_compiled_modules(
+    package_id: &str,
+    modules: &[CompiledModule],
+) -> GenericBoundsExport {
     let mut functions = BTreeMap::new();
     let mut structs = BTreeMap::new();
 
-    for def in m.function_defs() {
-        // RPC normalized modules include:
-        // - Public functions
-        // - Friend functions
-        // - Entry functions (even if private visibility)
-        // Skip non-entry private functions
-        if matches!(def.visibility, Visibility::Private) && !def.is_entry {
-            continue;
-        }
-
-        let handle = m.function_handle_at(def.function);
-        let name = m.identifier_at(handle.name).as_str().to_string();
-
-        let visibility = Some(visibility_to_string(def.visibility));
-        let is_entry = Some(def.is_entry);
-        let type_params = Some(handle.type_parameters.len());
-
-        let params_sig = m.signature_at(handle.parameters);
-        let params = params_sig
-            .0
-            .iter()
-            .map(|t| type_sig_from_token(m, t))
-            .collect::<Vec<_>>();
-
-        let returns_sig = m.signature_at(handle.return_);
-        let returns = returns_sig
-            .0
-            .iter()
-            .map(|t| type_sig_from_token(m, t))
-            .collect::<Vec<_>>();
-
-        functions.insert(
-            name,
-            FunctionInv {
-                visibility,
-                is_entry,
-                type_params,
-                params,
-                returns,
-            },
-        );
-    }
-
-    for def in m.struct_defs() {
-        let handle = m.datatype_handle_at(def.struct_handle);
-        let name = m.identifier_at(handle.name).as_str().to_string();
-        let abilities = abilities_to_vec(&handle.abilities);
-        let type_params = Some(handle.type_parameters.len());
+    for m in modules {
+        let mname = m.self_id().name().to_string();
 
-        let mut fields: Vec<(String, TypeSig)> = Vec::new();
-        if let Some(field_info) = def.fields() {
-            for f in field_info {
-                let fname = m.identifier_at(f.name).as_str().to_string();
-                let fty = type_sig_from_token(m, &f.signature.0);
-                fields.push((fname, fty));
+        for def in m.function_defs() {
+            if !matches!(def.visibility, Visibility::Public) {
+                continue;
+            }
+            let handle = m.function_handle_at(def.function);
+            if handle.type_parameters.is_empty() {
+                continue;
             }
+            let name = m.identifier_at(handle.name);
+            functions.insert(
+                format!("{}::{}", mname, name),
+                handle
+                    .type_parameters
+                    .iter()
+                    .map(abilities_to_vec)
+                    .collect(),
+            );
         }
-        fields.sort();
 
-        structs.insert(
-            name,
-            StructInv {
-                abilities,
-                type_params,
-                fields,
-            },
-        );
+        for def in m.struct_defs() {
+            let handle = m.datatype_handle_at(def.struct_handle);
+            if handle.type_parameters.is_empty() {
+                continue;
+            }
+            let name = m.identifier_at(handle.name);
+            structs.insert(
+                format!("{}::{}", mname, name),
+                handle
+                    .type_parameters
+                    .iter()
+                    .map(|tp| DatatypeTypeParamBounds {
+                        constraints: abilities_to_vec(&tp.constraints),
+                        is_phantom: tp.is_phantom,
+                    })
+                    .collect(),
+            );
+        }
     }
 
-    ModuleInventory { functions, structs }
-}
-
-fn package_inventory_from_compiled_modules(modules: &[CompiledModule]) -> PackageInventory {
-    let mut out = BTreeMap::new();
-    for m in modules {
-        let name = m.self_id().name().as_str().to_string();
-        out.insert(name, module_inventory_from_compiled_module(m));
+    GenericBoundsExport {
+        package_id: package_id.to_string(),
+        functions,
+        structs,
     }
-    PackageInventory { modules: out }
 }
 
 /// Compute detailed statistics from compiled modules matching extractor1's `local` field
-fn compute_local_stats(modules: &[CompiledModule]) -> LocalStats {
+fn compute_local_stats(modules: &[Arc<CompiledModule>]) -> LocalStats {
     let mut stats = LocalStats::default();
     stats.modules = modules.len();
 
@@ -795,175 +1259,162 @@ fn compute_rpc_stats(inv: &PackageInventory) -> RpcStats {
 
     stats
 }
-fn module_inventory_from_normalized_value(module: &Value) -> Result<ModuleInventory> {
-    let mut functions = BTreeMap::new();
-    let mut structs = BTreeMap::new();
-
-    // RPC uses "exposedFunctions" key, not "functions"
-    if let Some(funcs) = module.get("exposedFunctions").and_then(Value::as_object) {
-        for (fname, fval) in funcs {
-            let visibility = fval
-                .get("visibility")
-                .and_then(Value::as_str)
-                .map(|s| s.to_string());
 
-            // RPC uses camelCase: isEntry, typeParameters
-            let is_entry = fval.get("isEntry").and_then(Value::as_bool);
-
-            let type_params = fval
-                .get("typeParameters")
-                .and_then(Value::as_array)
-                .map(|a| a.len());
-
-            let params = fval
-                .get("parameters")
-                .and_then(Value::as_array)
-                .map(|a| a.iter().map(type_sig_from_value).collect())
-                .unwrap_or_default();
+/// Load a package with `load_compiled_modules_with_rpc_deps` and build the inventory of
+/// its own modules: those at the self address of the modules fetched for the package
+/// itself, which is its original id even for upgraded packages with no metadata.
+async fn load_own_package_inventory(
+    client: Arc<RpcClient>,
+    dataset: &DatasetOptions,
+    inventory: &InventoryOptions,
+    package_id: &str,
+) -> Result<PackageInventory> {
+    let loaded = load_compiled_modules_with_rpc_deps(client, dataset, package_id).await?;
+    let root_address = loaded
+        .root_address
+        .ok_or_else(|| anyhow!("package {} has no modules", package_id))?;
+    let own: Vec<Arc<CompiledModule>> = loaded
+        .modules
+        .into_iter()
+        .filter(|m| *m.address() == root_address)
+        .collect();
+    Ok(package_inventory_from_compiled_modules(&own, inventory))
+}
 
-            let returns = fval
-                .get("return")
-                .and_then(Value::as_array)
-                .map(|a| a.iter().map(type_sig_from_value).collect())
-                .unwrap_or_default();
+#[derive(Debug, Serialize)]
+struct BaselineDriftReport {
+    package_id: String,
+    ok: bool,
+    error: Option<String>,
+    #[serde(flatten)]
+    diff: PackageInventoryDiff,
+}
 
-            functions.insert(
-                fname.clone(),
-                FunctionInv {
-                    visibility,
-                    is_entry,
-                    type_params,
-                    params,
-                    returns,
-                },
-            );
-        }
-    }
+/// Diff every `<package_id>.json` baseline in `dir` (self) against the live RPC inventory
+/// (other).
+async fn check_baseline_dir(
+    client: Arc<RpcClient>,
+    inventory: &InventoryOptions,
+    dir: &Path,
+    sort_fields: bool,
+) -> Result<Vec<BaselineDriftReport>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("read_dir {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+        .collect();
+    paths.sort();
 
-    if let Some(sobjs) = module.get("structs").and_then(Value::as_object) {
-        for (sname, sval) in sobjs {
-            // RPC uses nested "abilities.abilities" with PascalCase values
-            let mut abilities = sval
-                .get("abilities")
-                .and_then(|v| v.get("abilities"))
-                .and_then(Value::as_array)
-                .map(|a| {
-                    a.iter()
-                        .filter_map(Value::as_str)
-                        .map(|s| s.to_lowercase()) // Normalize to lowercase
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or_default();
-            abilities.sort();
-
-            // RPC uses camelCase: typeParameters
-            let type_params = sval
-                .get("typeParameters")
-                .and_then(Value::as_array)
-                .map(|a| a.len());
-
-            let mut fields: Vec<(String, TypeSig)> = Vec::new();
-            if let Some(farr) = sval.get("fields").and_then(Value::as_array) {
-                for f in farr {
-                    let fname = f
-                        .get("name")
-                        .and_then(Value::as_str)
-                        .unwrap_or("<unknown>")
-                        .to_string();
-                    let fty = f
-                        .get("type")
-                        .map(type_sig_from_value)
-                        .unwrap_or(TypeSig("null".to_string()));
-                    fields.push((fname, fty));
+    let mut reports = Vec::with_capacity(paths.len());
+    for path in paths {
+        let package_id = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(s) => s.to_string(),
+            None => continue,
+        };
+        let mut baseline = load_package_inventory(&path)?;
+
+        let live = fetch_rpc_package_inventory(Arc::clone(&client), &package_id, inventory).await;
+        let report = match live {
+            Ok(mut live) => {
+                if sort_fields {
+                    sort_struct_fields(&mut baseline);
+                    sort_struct_fields(&mut live);
+                }
+                let diff = diff_package_inventory(&baseline, &live, false);
+                BaselineDriftReport {
+                    package_id,
+                    ok: diff.is_empty(),
+                    error: None,
+                    diff,
                 }
             }
-            fields.sort();
-
-            structs.insert(
-                sname.clone(),
-                StructInv {
-                    abilities,
-                    type_params,
-                    fields,
-                },
-            );
-        }
+            Err(e) => BaselineDriftReport {
+                package_id,
+                ok: false,
+                error: Some(format!("{e:#}")),
+                diff: PackageInventoryDiff::default(),
+            },
+        };
+        reports.push(report);
     }
-
-    Ok(ModuleInventory { functions, structs })
+    Ok(reports)
 }
 
-fn package_inventory_from_normalized_modules(modules_value: &Value) -> Result<PackageInventory> {
-    let mut modules = BTreeMap::new();
-    let Some(mobj) = modules_value.as_object() else {
-        return Err(anyhow!("expected modules to be an object"));
-    };
-
-    for (mname, mval) in mobj {
-        // RPC keys are module names; to compare with local bytecode, key by module self-name.
-        let name = mval
-            .get("name")
-            .and_then(Value::as_str)
-            .unwrap_or(mname)
-            .to_string();
-        modules.insert(name, module_inventory_from_normalized_value(mval)?);
-    }
-
-    Ok(PackageInventory { modules })
+#[derive(Debug, Serialize)]
+struct ThreeWayReport {
+    package_id: String,
+    error: Option<String>,
+    /// Differing item -> classification.
+    items: BTreeMap<String, &'static str>,
+    counts: BTreeMap<&'static str, usize>,
 }
 
-fn diff_module_inventory(
-    a: &ModuleInventory,
-    b: &ModuleInventory,
-) -> (bool, BTreeMap<String, usize>) {
-    let mut diffs: BTreeMap<String, usize> = BTreeMap::new();
+fn load_sui_packages_inventory(
+    dataset: &DatasetOptions,
+    inventory: &InventoryOptions,
+    sui_packages_dir: &Path,
+    package_id: &str,
+) -> Result<PackageInventory> {
+    let artifact_dir = artifact_dir_in_sui_packages(sui_packages_dir, dataset.network, package_id)?;
+    let modules = load_compiled_modules_from_bytecode_modules_dir(
+        dataset,
+        &artifact_dir.join("bytecode_modules"),
+    )?;
+    Ok(package_inventory_from_compiled_modules(&modules, inventory))
+}
 
-    for (k, va) in &a.functions {
-        match b.functions.get(k) {
-            None => {
-                *diffs
-                    .entry("function_missing_other".to_string())
-                    .or_default() += 1
-            }
-            Some(vb) => {
-                if va != vb {
-                    *diffs.entry("function_mismatch".to_string()).or_default() += 1;
-                }
-            }
-        }
-    }
-    for k in b.functions.keys() {
-        if !a.functions.contains_key(k) {
-            *diffs
-                .entry("function_missing_self".to_string())
-                .or_default() += 1;
+async fn three_way_compare(
+    client: Arc<RpcClient>,
+    dataset: &DatasetOptions,
+    inventory: &InventoryOptions,
+    current_dir: &Path,
+    old_dir: &Path,
+    package_id: &str,
+) -> ThreeWayReport {
+    let mut report = ThreeWayReport {
+        package_id: package_id.to_string(),
+        error: None,
+        items: BTreeMap::new(),
+        counts: BTreeMap::new(),
+    };
+    let inventories = (
+        load_sui_packages_inventory(dataset, inventory, current_dir, package_id)
+            .with_context(|| format!("current snapshot {}", current_dir.display())),
+        load_sui_packages_inventory(dataset, inventory, old_dir, package_id)
+            .with_context(|| format!("old snapshot {}", old_dir.display())),
+        fetch_rpc_package_inventory(client, package_id, inventory).await,
+    );
+    let (current, old, rpc) = match inventories {
+        (Ok(c), Ok(o), Ok(r)) => (
+            inventory_items(&c),
+            inventory_items(&o),
+            inventory_items(&r),
+        ),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            report.error = Some(format!("{e:#}"));
+            return report;
         }
-    }
+    };
 
-    for (k, va) in &a.structs {
-        match b.structs.get(k) {
-            None => *diffs.entry("struct_missing_other".to_string()).or_default() += 1,
-            Some(vb) => {
-                if va != vb {
-                    *diffs.entry("struct_mismatch".to_string()).or_default() += 1;
-                }
-            }
-        }
-    }
-    for k in b.structs.keys() {
-        if !a.structs.contains_key(k) {
-            *diffs.entry("struct_missing_self".to_string()).or_default() += 1;
+    let keys: BTreeSet<&String> = current.keys().chain(old.keys()).chain(rpc.keys()).collect();
+    for key in keys {
+        if let Some(class) = classify_three_way(current.get(key), old.get(key), rpc.get(key)) {
+            report.items.insert(key.clone(), class);
+            *report.counts.entry(class).or_default() += 1;
         }
     }
-
-    (diffs.is_empty(), diffs)
+    report
 }
+
 #[derive(Debug, Serialize, serde::Deserialize)]
 struct IndexMeta {
     source_jsonl: String,
     rows: usize,
     ok: usize,
     error: usize,
+    /// Unparseable lines dropped under `--tolerate-bad-lines`.
+    #[serde(default)]
+    skipped: usize,
 }
 
 #[derive(Debug, Serialize, serde::Deserialize)]
@@ -971,10 +1422,68 @@ struct IndexArtifacts {
     meta: IndexMeta,
     by_package_id: BTreeMap<String, u64>,
     errors: BTreeMap<String, u64>,
+    /// `FunctionInv::signature` -> package ids, from rows with `function_sigs`.
+    by_function_sig: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Count a summary row's `stackless_error` into `errors`; returns whether it had one.
+fn count_stackless_error(errors: &mut BTreeMap<String, u64>, row: &Value) -> bool {
+    match stackless_error_text(row) {
+        Some(err) => {
+            *errors.entry(err).or_insert(0) += 1;
+            true
+        }
+        None => false,
+    }
 }
 
+/// Aggregate of a batch run's summary rows, written by `--batch-stats-out`.
+#[derive(Debug, Default)]
+struct BatchStats {
+    total_packages: usize,
+    ok: usize,
+    total_modules: usize,
+    stackless_errors: BTreeMap<String, u64>,
+}
+
+impl BatchStats {
+    fn record(&mut self, row: &Value) {
+        self.total_packages += 1;
+        if row.get("ok") == Some(&Value::Bool(true)) {
+            self.ok += 1;
+        }
+        self.total_modules += row
+            .get("module_names")
+            .and_then(Value::as_array)
+            .map_or(0, Vec::len);
+        count_stackless_error(&mut self.stackless_errors, row);
+    }
+
+    /// The stats as JSON, with `stackless_errors` sorted by count (descending), then message.
+    fn to_json(&self) -> Value {
+        let mut histogram: Vec<(&String, &u64)> = self.stackless_errors.iter().collect();
+        histogram.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        json!({
+            "total_packages": self.total_packages,
+            "ok": self.ok,
+            "error": self.total_packages - self.ok,
+            "total_modules": self.total_modules,
+            "stackless_errors": histogram
+                .into_iter()
+                .map(|(error, count)| json!({"error": error, "count": count}))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Build index artifacts from a summary JSONL. When `previous` is given, its first
+/// `meta.rows + meta.skipped` lines are assumed already indexed and only later rows are
+/// merged in. With `tolerate_bad_lines`, unparseable lines are logged and counted in
+/// `meta.skipped` instead of failing the build.
 fn build_index_from_summary_jsonl(
     summary_jsonl_path: &std::path::Path,
+    previous: Option<IndexArtifacts>,
+    tolerate_bad_lines: bool,
 ) -> anyhow::Result<IndexArtifacts> {
     let file = std::fs::File::open(summary_jsonl_path).with_context(|| {
         format!(
@@ -988,6 +1497,19 @@ fn build_index_from_summary_jsonl(
     let mut ok = 0usize;
     let mut by_package_id: BTreeMap<String, u64> = BTreeMap::new();
     let mut errors: BTreeMap<String, u64> = BTreeMap::new();
+    let mut by_function_sig: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut skipped = 0usize;
+    let mut already_indexed = 0usize;
+    if let Some(previous) = previous {
+        rows = previous.meta.rows;
+        ok = previous.meta.ok;
+        skipped = previous.meta.skipped;
+        by_package_id = previous.by_package_id;
+        errors = previous.errors;
+        by_function_sig = previous.by_function_sig;
+        already_indexed = rows + skipped;
+    }
+    let mut seen = 0usize;
 
     for line in std::io::BufRead::lines(reader) {
         let line = line?;
@@ -996,9 +1518,25 @@ fn build_index_from_summary_jsonl(
             continue;
         }
 
+        seen += 1;
+        if seen <= already_indexed {
+            continue;
+        }
+
+        let v: serde_json::Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(e) if tolerate_bad_lines => {
+                tracing::warn!(line = seen, error = %e, "skipping unparseable summary line");
+                skipped += 1;
+                continue;
+            }
+            Err(e) => {
+                return Err(
+                    anyhow::Error::new(e).context(format!("failed to parse jsonl line {}", seen))
+                )
+            }
+        };
         rows += 1;
-        let v: serde_json::Value = serde_json::from_str(line)
-            .with_context(|| format!("failed to parse jsonl line {}", rows))?;
         let package_id = v
             .get("resolved_package_id")
             .and_then(|x| x.as_str())
@@ -1006,6 +1544,15 @@ fn build_index_from_summary_jsonl(
             .unwrap_or("<missing>")
             .to_string();
 
+        if let Some(sigs) = v.get("function_sigs").and_then(Value::as_object) {
+            for sig in sigs.values().filter_map(Value::as_str) {
+                by_function_sig
+                    .entry(squash_function_sig(sig))
+                    .or_default()
+                    .insert(package_id.clone());
+            }
+        }
+
         match by_package_id.entry(package_id) {
             btree_map::Entry::Vacant(e) => {
                 e.insert(rows as u64);
@@ -1013,23 +1560,52 @@ fn build_index_from_summary_jsonl(
             btree_map::Entry::Occupied(_) => {}
         }
 
-        let err = v.get("stackless_error").and_then(|x| x.as_str());
-        if let Some(err) = err {
-            *errors.entry(err.to_string()).or_insert(0) += 1;
-        } else {
+        if !count_stackless_error(&mut errors, &v) {
             ok += 1;
         }
     }
 
+    if seen < already_indexed {
+        return Err(anyhow!(
+            "summary jsonl {} has {} rows but the existing index covers {}; rebuild without --index-append",
+            summary_jsonl_path.display(),
+            seen,
+            already_indexed
+        ));
+    }
+
     Ok(IndexArtifacts {
         meta: IndexMeta {
             source_jsonl: summary_jsonl_path.display().to_string(),
             rows,
             ok,
             error: rows.saturating_sub(ok),
+            skipped,
         },
         by_package_id,
         errors,
+        by_function_sig,
+    })
+}
+
+/// Load index artifacts previously written by `write_index_artifacts`.
+fn read_index_artifacts(out_dir: &std::path::Path) -> anyhow::Result<IndexArtifacts> {
+    fn read_json<T: serde::de::DeserializeOwned>(path: &std::path::Path) -> anyhow::Result<T> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_slice(&bytes)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    Ok(IndexArtifacts {
+        meta: read_json(&out_dir.join("meta.json"))?,
+        by_package_id: read_json(&out_dir.join("by_package_id.json"))?,
+        errors: read_json(&out_dir.join("errors.json"))?,
+        // Indexes built before signatures were embedded have no such file.
+        by_function_sig: match out_dir.join("by_function_sig.json") {
+            path if path.exists() => read_json(&path)?,
+            _ => BTreeMap::new(),
+        },
     })
 }
 
@@ -1037,24 +1613,162 @@ fn write_index_artifacts(index: &IndexArtifacts, out_dir: &std::path::Path) -> a
     std::fs::create_dir_all(out_dir)
         .with_context(|| format!("failed to create out dir: {}", out_dir.display()))?;
 
-    let meta_path = out_dir.join("meta.json");
-    std::fs::write(&meta_path, serde_json::to_vec_pretty(&index.meta)?)
-        .with_context(|| format!("failed to write {}", meta_path.display()))?;
-
     let by_pkg_path = out_dir.join("by_package_id.json");
-    std::fs::write(
+    write_file_atomic(
         &by_pkg_path,
-        serde_json::to_vec_pretty(&index.by_package_id)?,
-    )
-    .with_context(|| format!("failed to write {}", by_pkg_path.display()))?;
+        &serde_json::to_vec_pretty(&index.by_package_id)?,
+    )?;
 
     let errors_path = out_dir.join("errors.json");
-    std::fs::write(&errors_path, serde_json::to_vec_pretty(&index.errors)?)
-        .with_context(|| format!("failed to write {}", errors_path.display()))?;
+    write_file_atomic(&errors_path, &serde_json::to_vec_pretty(&index.errors)?)?;
+
+    let sigs_path = out_dir.join("by_function_sig.json");
+    write_file_atomic(
+        &sigs_path,
+        &serde_json::to_vec_pretty(&index.by_function_sig)?,
+    )?;
+
+    // Written last: `--index-append` trusts `meta.rows`, so it must not get ahead of the
+    // other files if the run dies part way.
+    let meta_path = out_dir.join("meta.json");
+    write_file_atomic(&meta_path, &serde_json::to_vec_pretty(&index.meta)?)?;
 
     Ok(())
 }
 
+/// Write `<out_dir>/index.db` from a summary JSONL, replacing any previous contents.
+/// `packages` holds the first row of each package id; `errors` counts `stackless_error`s.
+fn write_index_sqlite(
+    summary_jsonl_path: &Path,
+    out_dir: &Path,
+    tolerate_bad_lines: bool,
+) -> anyhow::Result<PathBuf> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("failed to create out dir: {}", out_dir.display()))?;
+    let db_path = out_dir.join("index.db");
+    let file = std::fs::File::open(summary_jsonl_path).with_context(|| {
+        format!(
+            "failed to open summary jsonl: {}",
+            summary_jsonl_path.display()
+        )
+    })?;
+    let reader = std::io::BufReader::new(file);
+
+    let mut conn = rusqlite::Connection::open(&db_path)
+        .with_context(|| format!("failed to open sqlite db: {}", db_path.display()))?;
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS packages;
+         DROP TABLE IF EXISTS errors;
+         CREATE TABLE packages (
+             package_id TEXT PRIMARY KEY,
+             first_row INTEGER NOT NULL,
+             ok INTEGER NOT NULL,
+             error TEXT
+         );
+         CREATE TABLE errors (error TEXT PRIMARY KEY, count INTEGER NOT NULL);",
+    )?;
+
+    let tx = conn.transaction()?;
+    {
+        let mut insert_package = tx.prepare(
+            "INSERT OR IGNORE INTO packages (package_id, first_row, ok, error)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let mut count_error = tx.prepare(
+            "INSERT INTO errors (error, count) VALUES (?1, 1)
+             ON CONFLICT(error) DO UPDATE SET count = count + 1",
+        )?;
+        let mut rows = 0usize;
+        let mut seen = 0usize;
+        for line in std::io::BufRead::lines(reader) {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            seen += 1;
+            let v: serde_json::Value = match serde_json::from_str(line) {
+                Ok(v) => v,
+                Err(e) if tolerate_bad_lines => {
+                    tracing::warn!(line = seen, error = %e, "skipping unparseable summary line");
+                    continue;
+                }
+                Err(e) => {
+                    return Err(anyhow::Error::new(e)
+                        .context(format!("failed to parse jsonl line {}", seen)))
+                }
+            };
+            rows += 1;
+            let package_id = v
+                .get("resolved_package_id")
+                .and_then(|x| x.as_str())
+                .or_else(|| v.get("package_id").and_then(|x| x.as_str()))
+                .unwrap_or("<missing>");
+            let err = stackless_error_text(&v);
+            insert_package.execute(rusqlite::params![
+                package_id,
+                rows as i64,
+                err.is_none(),
+                err
+            ])?;
+            if let Some(err) = err {
+                count_error.execute([err])?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(db_path)
+}
+
+/// Merge summary JSONL files in order, deduplicating by package id (last row wins, kept
+/// at the position of the first occurrence). Differing duplicates are logged to stderr.
+fn merge_summary_jsonl_files(paths: &[PathBuf]) -> anyhow::Result<Vec<Value>> {
+    let mut rows: Vec<Value> = Vec::new();
+    let mut position: BTreeMap<String, usize> = BTreeMap::new();
+    for path in paths {
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        for (lineno, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let v: Value = serde_json::from_str(line).with_context(|| {
+                format!("failed to parse {} line {}", path.display(), lineno + 1)
+            })?;
+            let Some(package_id) = v
+                .get("resolved_package_id")
+                .or_else(|| v.get("package_id"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+            else {
+                return Err(anyhow!(
+                    "{} line {} has no resolved_package_id",
+                    path.display(),
+                    lineno + 1
+                ));
+            };
+            match position.entry(package_id) {
+                btree_map::Entry::Vacant(e) => {
+                    e.insert(rows.len());
+                    rows.push(v);
+                }
+                btree_map::Entry::Occupied(e) => {
+                    let existing = &mut rows[*e.get()];
+                    if *existing != v {
+                        tracing::warn!(
+                            package_id = %e.key(),
+                            kept = %format!("{}:{}", path.display(), lineno + 1),
+                            "conflicting summary rows"
+                        );
+                    }
+                    *existing = v;
+                }
+            }
+        }
+    }
+    Ok(rows)
+}
+
 fn read_package_ids_from_summary_jsonl(
     summary_jsonl_path: &std::path::Path,
 ) -> anyhow::Result<Vec<String>> {
@@ -1073,225 +1787,868 @@ fn read_package_ids_from_summary_jsonl(
         if line.is_empty() {
             continue;
         }
-        if let Ok(v) = serde_json::from_str::<serde_json::Value>(line) {
-            if let Some(id) = v
-                .get("resolved_package_id")
-                .and_then(|x| x.as_str())
-                .or_else(|| v.get("package_id").and_then(|x| x.as_str()))
-            {
-                ids.push(id.to_string());
+        if let Ok(v) = serde_json::from_str::<serde_json::Value>(line) {
+            if let Some(id) = v
+                .get("resolved_package_id")
+                .and_then(|x| x.as_str())
+                .or_else(|| v.get("package_id").and_then(|x| x.as_str()))
+            {
+                ids.push(id.to_string());
+            }
+        }
+    }
+    ids.sort();
+    ids.dedup();
+    Ok(ids)
+}
+
+/// Build a `address::module::Name -> [package_id]` index over the struct and enum
+/// definitions of every package in the local dataset.
+///
+/// Keys use the module self-address, so all versions of an upgraded package map the
+/// same type name and each of them is listed.
+fn build_type_definition_index(
+    dataset: &DatasetOptions,
+    limit: usize,
+) -> Result<BTreeMap<String, Vec<String>>> {
+    let mut index: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    for package_id in iter_dataset_package_ids(dataset, limit)? {
+        let modules = match try_load_local_modules_for_package(dataset, &package_id) {
+            Ok(Some(v)) => v,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!(%package_id, error = %format!("{e:#}"), "skipping in type index");
+                continue;
+            }
+        };
+
+        for m in &modules {
+            let self_id = m.self_id();
+            let prefix = format!("{}::{}", self_id.address().to_hex_literal(), self_id.name());
+            let handles = m
+                .struct_defs()
+                .iter()
+                .map(|def| def.struct_handle)
+                .chain(m.enum_defs().iter().map(|def| def.enum_handle));
+            for idx in handles {
+                let name = m.identifier_at(m.datatype_handle_at(idx).name);
+                index
+                    .entry(format!("{}::{}", prefix, name))
+                    .or_default()
+                    .insert(package_id.clone());
+            }
+        }
+    }
+
+    Ok(index
+        .into_iter()
+        .map(|(k, v)| (k, v.into_iter().collect()))
+        .collect())
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+struct ParamConventionCounts {
+    by_value: usize,
+    by_ref: usize,
+    by_mut_ref: usize,
+}
+
+impl ParamConventionCounts {
+    fn add(&mut self, other: &Self) {
+        self.by_value += other.by_value;
+        self.by_ref += other.by_ref;
+        self.by_mut_ref += other.by_mut_ref;
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ParamConventionStats {
+    packages: BTreeMap<String, ParamConventionCounts>,
+    total: ParamConventionCounts,
+}
+
+/// Classify the parameters of every public function by their outermost token.
+fn param_conventions_from_compiled_modules(modules: &[CompiledModule]) -> ParamConventionCounts {
+    let mut counts = ParamConventionCounts::default();
+    for m in modules {
+        for def in m.function_defs() {
+            if !matches!(def.visibility, Visibility::Public) {
+                continue;
+            }
+            let handle = m.function_handle_at(def.function);
+            for token in &m.signature_at(handle.parameters).0 {
+                match token {
+                    SignatureToken::Reference(_) => counts.by_ref += 1,
+                    SignatureToken::MutableReference(_) => counts.by_mut_ref += 1,
+                    _ => counts.by_value += 1,
+                }
+            }
+        }
+    }
+    counts
+}
+
+fn build_param_convention_stats(
+    dataset: &DatasetOptions,
+    limit: usize,
+) -> Result<ParamConventionStats> {
+    let mut stats = ParamConventionStats {
+        packages: BTreeMap::new(),
+        total: ParamConventionCounts::default(),
+    };
+    for package_id in iter_dataset_package_ids(dataset, limit)? {
+        let modules = match try_load_local_modules_for_package(dataset, &package_id) {
+            Ok(Some(v)) => v,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!(
+                    %package_id,
+                    error = %format!("{e:#}"),
+                    "skipping in param convention stats"
+                );
+                continue;
+            }
+        };
+        let counts = param_conventions_from_compiled_modules(&modules);
+        stats.total.add(&counts);
+        stats.packages.insert(package_id, counts);
+    }
+    Ok(stats)
+}
+
+/// Hash a package's raw `.mv` bytes, in file-name order, into a sha256 hex digest.
+fn package_bytes_hash(bytecode_modules_dir: &Path) -> Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(bytecode_modules_dir)
+        .with_context(|| format!("read_dir {}", bytecode_modules_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("mv") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut hasher = Sha256::new();
+    for path in &paths {
+        let bytes = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+        // Length-prefix each module so concatenation boundaries are unambiguous.
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[derive(Debug, Serialize)]
+struct DuplicatePackageGroup {
+    bytes_sha256: String,
+    package_ids: Vec<String>,
+}
+
+/// Group local dataset packages that publish byte-identical module sets under different ids.
+fn find_duplicate_packages(
+    dataset: &DatasetOptions,
+    limit: usize,
+) -> Result<Vec<DuplicatePackageGroup>> {
+    let mut by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for package_id in iter_dataset_package_ids(dataset, limit)? {
+        let bytecode_dir = match sui_packages_artifact_dir_for_package_id(dataset, &package_id)
+            .and_then(|p| p.canonicalize().map_err(|e| anyhow!(e)))
+        {
+            Ok(p) => p.join("bytecode_modules"),
+            Err(_) => continue,
+        };
+        match package_bytes_hash(&bytecode_dir) {
+            Ok(hash) => by_hash.entry(hash).or_default().push(package_id),
+            Err(e) => tracing::warn!(
+                %package_id,
+                error = %format!("{e:#}"),
+                "skipping in duplicate scan"
+            ),
+        }
+    }
+
+    Ok(by_hash
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|(bytes_sha256, package_ids)| DuplicatePackageGroup {
+            bytes_sha256,
+            package_ids,
+        })
+        .collect())
+}
+
+type PanicHook = Box<dyn Fn(&std::panic::PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+/// `(active callers, hook to restore)` for `catch_unwind_silent`.
+static SILENT_PANIC_HOOK: std::sync::Mutex<(usize, Option<PanicHook>)> =
+    std::sync::Mutex::new((0, None));
+
+/// `catch_unwind` with the panic hook silenced. Hook swaps are reference counted so
+/// concurrent callers don't restore each other's silent hook as the "previous" one.
+fn catch_unwind_silent<R>(
+    f: impl FnOnce() -> R + std::panic::UnwindSafe,
+) -> std::thread::Result<R> {
+    {
+        let mut state = SILENT_PANIC_HOOK.lock().unwrap_or_else(|e| e.into_inner());
+        if state.0 == 0 {
+            state.1 = Some(std::panic::take_hook());
+            std::panic::set_hook(Box::new(|_| {}));
+        }
+        state.0 += 1;
+    }
+
+    let result = std::panic::catch_unwind(f);
+
+    let mut state = SILENT_PANIC_HOOK.lock().unwrap_or_else(|e| e.into_inner());
+    state.0 -= 1;
+    if state.0 == 0 {
+        if let Some(prev_hook) = state.1.take() {
+            std::panic::set_hook(prev_hook);
+        }
+    }
+    result
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic>".to_string()
+    }
+}
+
+/// Settings of a local dataset run, derived from the CLI.
+#[derive(Debug, Clone)]
+struct LocalRunOptions {
+    dataset: DatasetOptions,
+    inventory: InventoryOptions,
+    /// `--isolate-stackless-failures`
+    isolate_stackless_failures: bool,
+}
+
+impl LocalRunOptions {
+    fn from_args(args: &Args) -> Self {
+        Self {
+            dataset: args.dataset_options(),
+            inventory: args.inventory_options(),
+            isolate_stackless_failures: args.isolate_stackless_failures,
+        }
+    }
+}
+
+/// `--print-rpc-method-timings`: per-method RPC calls and time, then the run totals.
+fn print_rpc_method_timings(metrics: &RpcMetrics) {
+    eprintln!("RPC method timings:");
+    for (method, m) in &metrics.methods {
+        eprintln!(
+            "  {:<48} calls={:<8} total={:.3}s avg={:.1}ms",
+            method,
+            m.calls,
+            m.total_ms as f64 / 1000.0,
+            m.total_ms as f64 / m.calls.max(1) as f64
+        );
+    }
+    eprintln!(
+        "  {:<48} calls={:<8} cache_hits={} cache_misses={}",
+        "(all)", metrics.rpc_calls, metrics.cache_hits, metrics.cache_misses
+    );
+}
+
+/// Build the dataset's related-id index on a blocking thread before packages fan out, so
+/// its metadata scan doesn't run on (and stall) a runtime worker mid-run.
+async fn prepare_dataset_index(dataset: &DatasetOptions) -> Result<()> {
+    let dataset = dataset.clone();
+    tokio::task::spawn_blocking(move || prepare_related_ids(&dataset))
+        .await
+        .context("join related id index")
+}
+
+/// Load a dataset package with its RPC deps and translate it to stackless bytecode. With
+/// `embed_sigs`, the root modules' `function_signatures` are built from the same loaded
+/// modules.
+async fn run_single_local_sui_packages_with_rpc_deps(
+    client: Arc<RpcClient>,
+    opts: &LocalRunOptions,
+    package_id: &str,
+    embed_sigs: bool,
+) -> Result<LocalBytecodeModuleList> {
+    let artifact_dir = sui_packages_artifact_dir_for_package_id(&opts.dataset, package_id)?;
+    let resolved = artifact_dir
+        .canonicalize()
+        .with_context(|| format!("canonicalize {}", artifact_dir.display()))?;
+
+    let bytecode_modules_dir = resolved.join("bytecode_modules");
+    let module_names = extract_module_names_from_bytecode_modules_dir(
+        &opts.dataset,
+        &opts.inventory,
+        &bytecode_modules_dir,
+    )?;
+
+    let loaded =
+        load_compiled_modules_with_rpc_deps(Arc::clone(&client), &opts.dataset, package_id).await?;
+    let skipped_modules = loaded.skipped_modules;
+    let version = loaded.version;
+    let dep_graph = loaded.dep_graph;
+    let root_address = loaded.root_address;
+    let compiled_modules = loaded.modules;
+
+    // Translation is CPU-bound; keep it off the async workers so concurrent batch jobs
+    // don't starve RPC futures.
+    let structs = struct_count(&compiled_modules);
+    let isolate = opts.isolate_stackless_failures;
+    let inventory = opts.inventory.clone();
+    let (translated, stackless_failed_modules, function_sigs) =
+        tokio::task::spawn_blocking(move || {
+            let function_sigs = embed_sigs.then(|| {
+                let root: Vec<Arc<CompiledModule>> = compiled_modules
+                    .iter()
+                    .filter(|m| Some(*m.address()) == root_address)
+                    .cloned()
+                    .collect();
+                function_signatures(&root, &inventory)
+            });
+            // `from_compiled_modules` only takes owned modules. Modules no one else holds are
+            // moved out; only those shared with the framework cache, or kept for isolation,
+            // are copied.
+            let for_isolation =
+                (isolate && root_address.is_some()).then(|| compiled_modules.clone());
+            let owned: Vec<CompiledModule> = compiled_modules
+                .into_iter()
+                .map(Arc::unwrap_or_clone)
+                .collect();
+            let translated = match catch_unwind_silent(move || {
+                from_compiled_modules(owned, /* optimize */ true)
+            }) {
+                Ok(Ok((_model, stackless, stats))) => {
+                    Ok(count_stackless_with_stats(&stackless, stats, structs))
+                }
+                Ok(Err(e)) => Err(StacklessError::Error(format!("{e:#}"))),
+                Err(panic_payload) => {
+                    Err(StacklessError::Panic(panic_message(panic_payload.as_ref())))
+                }
+            };
+            let failed_modules = match (root_address, for_isolation) {
+                (Some(root), Some(modules)) if translated.is_err() => {
+                    isolate_stackless_failures(&modules, root)
+                }
+                _ => Vec::new(),
+            };
+            (translated, failed_modules, function_sigs)
+        })
+        .await
+        .context("join stackless translation")?;
+    let (stackless_summary, stackless_error) = match translated {
+        Ok(summary) => (Some(summary), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    Ok(LocalBytecodeModuleList {
+        package_id: package_id.to_string(),
+        dataset: format!("sui-packages/{}", opts.dataset.network.dataset_subdir()),
+        resolved_artifact_dir: resolved.display().to_string(),
+        resolved_bytecode_modules_dir: bytecode_modules_dir.display().to_string(),
+        module_names,
+        version,
+        skipped_modules,
+        stackless_summary,
+        stackless_error,
+        stackless_failed_modules,
+        function_sigs,
+        dep_graph,
+    })
+}
+
+/// After a whole-package translation failure, translate each root module (those at
+/// `root_address`) together with its transitive dependencies from `modules`, and return the
+/// names of the root modules whose translation still errors or panics.
+fn isolate_stackless_failures(
+    modules: &[Arc<CompiledModule>],
+    root_address: AccountAddress,
+) -> Vec<String> {
+    let root_ids: BTreeSet<ModuleId> = modules
+        .iter()
+        .filter(|m| *m.address() == root_address)
+        .map(|m| m.self_id())
+        .collect();
+    let by_id: BTreeMap<ModuleId, &CompiledModule> =
+        modules.iter().map(|m| (m.self_id(), &**m)).collect();
+
+    let mut failed = Vec::new();
+    for root_id in &root_ids {
+        let mut closure = BTreeSet::new();
+        let mut stack = vec![root_id.clone()];
+        while let Some(id) = stack.pop() {
+            if !closure.insert(id.clone()) {
+                continue;
+            }
+            if let Some(m) = by_id.get(&id) {
+                stack.extend(m.immediate_dependencies());
+            }
+        }
+        let subset: Vec<CompiledModule> = closure
+            .iter()
+            .filter_map(|id| by_id.get(id).map(|m| (*m).clone()))
+            .collect();
+        let ok = matches!(
+            catch_unwind_silent(move || from_compiled_modules(subset, /* optimize */ true)),
+            Ok(Ok(_))
+        );
+        if !ok {
+            failed.push(root_id.name().to_string());
+        }
+    }
+    failed
+}
+
+/// Write `bytes` to a sibling temp file and rename it over `path`, so readers only ever
+/// see the old or the new contents.
+fn write_file_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("not a file path: {}", path.display()))?;
+    let mut tmp_name = file_name.to_os_string();
+    tmp_name.push(format!(".tmp.{}", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+    fs::write(&tmp_path, bytes).with_context(|| format!("write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("rename {} -> {}", tmp_path.display(), path.display()))?;
+    Ok(())
+}
+
+/// Sibling `<name>.tmp` that a streamed output is written to until `commit_partial_output`
+/// renames it into place, so an interrupted run never leaves a truncated `path`.
+fn partial_output_path(path: &Path) -> PathBuf {
+    let mut name = path
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+fn create_partial_output(path: &Path) -> Result<fs::File> {
+    let tmp_path = partial_output_path(path);
+    fs::File::create(&tmp_path).with_context(|| format!("create {}", tmp_path.display()))
+}
+
+fn commit_partial_output(path: &Path) -> Result<()> {
+    let tmp_path = partial_output_path(path);
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("rename {} -> {}", tmp_path.display(), path.display()))
+}
+
+#[derive(Debug, Default, Serialize)]
+struct CheckpointState {
+    processed: usize,
+    total: usize,
+    ok: usize,
+    failed: usize,
+    current_package: Option<String>,
+    elapsed_secs: f64,
+}
+
+/// `--checkpoint-file` progress reporter, shareable across concurrent tasks. Every method
+/// is a no-op without a path; writes are throttled to one per second.
+struct Checkpoint {
+    path: Option<PathBuf>,
+    start: Instant,
+    state: std::sync::Mutex<(CheckpointState, Option<Instant>)>,
+}
+
+impl Checkpoint {
+    const MIN_WRITE_INTERVAL: Duration = Duration::from_secs(1);
+
+    fn new(path: Option<PathBuf>, total: usize) -> Self {
+        Self {
+            path,
+            start: Instant::now(),
+            state: std::sync::Mutex::new((
+                CheckpointState {
+                    total,
+                    ..Default::default()
+                },
+                None,
+            )),
+        }
+    }
+
+    fn begin(&self, package_id: &str) {
+        self.update(false, |s| s.current_package = Some(package_id.to_string()));
+    }
+
+    fn record(&self, ok: bool) {
+        self.update(false, |s| {
+            s.processed += 1;
+            if ok {
+                s.ok += 1;
+            } else {
+                s.failed += 1;
             }
-        }
+        });
     }
-    ids.sort();
-    ids.dedup();
-    Ok(ids)
-}
 
-fn sui_packages_artifact_dir_for_package_id(package_id: &str) -> Result<PathBuf> {
-    let package_id = package_id.strip_prefix("0x").unwrap_or(package_id);
-    // Left-pad with zeros to 64 hex chars if needed (handles short addresses like 0x2 or 63-char ids).
-    let package_id = format!("{:0>64}", package_id);
-    if package_id.len() != 64 || !package_id.chars().all(|c| c.is_ascii_hexdigit()) {
-        return Err(anyhow!(
-            "expected 64-hex package id (optionally 0x-prefixed), got: {}",
-            package_id
-        ));
+    fn finish(&self) {
+        self.update(true, |s| s.current_package = None);
     }
 
-    let prefix = &package_id[0..2];
-    let entry_name = &package_id[2..];
+    fn update(&self, force: bool, f: impl FnOnce(&mut CheckpointState)) {
+        let Some(path) = self.path.as_ref() else {
+            return;
+        };
+        let mut guard = self.state.lock().expect("checkpoint lock");
+        let (state, last_write) = &mut *guard;
+        f(state);
+        if !force && last_write.is_some_and(|t| t.elapsed() < Self::MIN_WRITE_INTERVAL) {
+            return;
+        }
+        state.elapsed_secs = self.start.elapsed().as_secs_f64();
+        let result = serde_json::to_vec_pretty(state)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| write_file_atomic(path, &bytes));
+        match result {
+            Ok(()) => *last_write = Some(Instant::now()),
+            Err(e) => tracing::warn!(error = %format!("{e:#}"), "failed to write checkpoint"),
+        }
+    }
+}
 
-    // Dataset layout is `$SUI_PACKAGES_DIR/packages/mainnet_most_used/0x??/<suffix62>`
-    // Default: ../sui-packages (relative to cwd)
-    let sui_packages_dir =
-        std::env::var("SUI_PACKAGES_DIR").unwrap_or_else(|_| "../sui-packages".to_string());
-    Ok(PathBuf::from(format!(
-        "{}/packages/mainnet_most_used/0x{}/{}",
-        sui_packages_dir, prefix, entry_name
-    )))
+/// Append one JSON line to a writer shared between concurrent tasks.
+fn write_jsonl_row<W: Write>(out: &std::sync::Mutex<W>, row: &impl Serialize) -> Result<()> {
+    let mut out = out.lock().expect("jsonl writer lock");
+    serde_json::to_writer(&mut *out, row)?;
+    out.write_all(b"\n")?;
+    Ok(())
 }
 
-/// Read the originalPackageId from metadata.json if present. Falls back to the passed id.
-fn read_original_package_id_from_metadata(package_id: &str) -> Result<String> {
-    let artifact_dir = sui_packages_artifact_dir_for_package_id(package_id)?
-        .canonicalize()
-        .ok();
-    let artifact_dir = match artifact_dir {
-        Some(d) => d,
-        None => return Ok(package_id.to_string()),
+/// Package ids already in a batch summary JSONL, and the byte length of its intact prefix.
+/// Unparseable trailing lines (an interrupted write) fall outside that prefix so appended
+/// rows start on a clean line; an unparseable line followed by good ones is an error.
+fn read_processed_summary_ids(path: &Path) -> Result<(BTreeSet<String>, u64)> {
+    let bytes = match fs::read(path) {
+        Ok(b) => b,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((BTreeSet::new(), 0)),
+        Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
     };
-    let meta_path = artifact_dir.join("metadata.json");
-    if !meta_path.exists() {
-        return Ok(package_id.to_string());
+    let mut ids = BTreeSet::new();
+    let mut intact_len = 0usize;
+    let mut offset = 0usize;
+    let mut first_bad_line: Option<usize> = None;
+    for (lineno, line) in bytes.split_inclusive(|&b| b == b'\n').enumerate() {
+        offset += line.len();
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        // A row without its newline was cut off mid-write, even if it happens to parse.
+        match serde_json::from_slice::<Value>(line) {
+            Ok(v) if line.ends_with(b"\n") => {
+                if let Some(bad) = first_bad_line {
+                    return Err(anyhow!(
+                        "unparseable line {} in {} is not at the end; fix or remove it before --resume",
+                        bad,
+                        path.display()
+                    ));
+                }
+                if let Some(id) = v.get("resolved_package_id").and_then(Value::as_str) {
+                    ids.insert(id.to_string());
+                }
+                intact_len = offset;
+            }
+            _ => {
+                first_bad_line.get_or_insert(lineno + 1);
+            }
+        }
     }
-    let data = fs::read_to_string(&meta_path)?;
-    let v: serde_json::Value = serde_json::from_str(&data)?;
-    if let Some(orig) = v.get("originalPackageId").and_then(|v| v.as_str()) {
-        return Ok(orig.to_string());
+    if intact_len < bytes.len() {
+        tracing::warn!(
+            path = %path.display(),
+            dropped_bytes = bytes.len() - intact_len,
+            "ignoring incomplete trailing summary rows"
+        );
     }
-    Ok(package_id.to_string())
+    Ok((ids, intact_len as u64))
 }
 
-fn iter_mainnet_most_used_package_ids(limit: usize) -> Result<Vec<String>> {
-    let sui_packages_dir =
-        std::env::var("SUI_PACKAGES_DIR").unwrap_or_else(|_| "../sui-packages".to_string());
-    let dataset_root = PathBuf::from(format!("{}/packages/mainnet_most_used", sui_packages_dir));
+/// Copy a dataset package's `.mv` files and `metadata.json` into `<out_dir>/<package_id>/`.
+fn dump_failing_bytecode(
+    dataset: &DatasetOptions,
+    out_dir: &Path,
+    package_id: &str,
+) -> Result<PathBuf> {
+    let artifact_dir = sui_packages_artifact_dir_for_package_id(dataset, package_id)?
+        .canonicalize()
+        .with_context(|| format!("canonicalize artifact dir for {}", package_id))?;
+    let bundle_dir = out_dir.join(package_id);
+    fs::create_dir_all(&bundle_dir).with_context(|| format!("create {}", bundle_dir.display()))?;
 
-    let mut entries: Vec<(String, String)> = Vec::new();
-    for prefix_dir in fs::read_dir(&dataset_root)
-        .with_context(|| format!("read_dir {}", dataset_root.display()))?
+    let bytecode_modules_dir = artifact_dir.join("bytecode_modules");
+    for entry in fs::read_dir(&bytecode_modules_dir)
+        .with_context(|| format!("read_dir {}", bytecode_modules_dir.display()))?
     {
-        let prefix_dir = prefix_dir?;
-        let prefix_path = prefix_dir.path();
-        if !prefix_path.is_dir() {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("mv") {
             continue;
         }
-        let prefix_name = prefix_path
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or_default()
-            .to_string();
-        if !prefix_name.starts_with("0x") {
-            continue;
+        if let Some(name) = path.file_name() {
+            fs::copy(&path, bundle_dir.join(name))
+                .with_context(|| format!("copy {}", path.display()))?;
         }
-        for pkg_dir in fs::read_dir(&prefix_path)? {
-            let pkg_dir = pkg_dir?;
-            let pkg_path = pkg_dir.path();
-            let pkg_name = pkg_path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or_default()
-                .to_string();
-            if pkg_name.is_empty() {
-                continue;
-            }
-            entries.push((prefix_name.clone(), pkg_name));
+    }
+    let metadata_path = artifact_dir.join("metadata.json");
+    if metadata_path.exists() {
+        fs::copy(&metadata_path, bundle_dir.join("metadata.json"))
+            .with_context(|| format!("copy {}", metadata_path.display()))?;
+    }
+    Ok(bundle_dir)
+}
+
+/// `dump_failing_bytecode` when a dump dir is configured, warning instead of failing.
+fn maybe_dump_failing_bytecode(dataset: &DatasetOptions, out_dir: Option<&Path>, package_id: &str) {
+    if let Some(out_dir) = out_dir {
+        match dump_failing_bytecode(dataset, out_dir, package_id) {
+            Ok(dir) => tracing::info!(%package_id, dir = %dir.display(), "dumped failing bytecode"),
+            Err(e) => tracing::warn!(
+                %package_id,
+                error = %format!("{e:#}"),
+                "failed to dump bytecode"
+            ),
         }
     }
+}
 
-    entries.sort();
+/// Columns of `--summary-format csv`.
+const BATCH_SUMMARY_CSV_FIELDS: [&str; 4] = [
+    "resolved_package_id",
+    "ok",
+    "module_count",
+    "stackless_error",
+];
+
+/// CSV values of a batch summary row. Rows that failed before stackless analysis report
+/// their `error` (or `skipped` reason) in the `stackless_error` column.
+fn batch_summary_csv_values(row: &Value) -> Vec<String> {
+    let str_field = |key: &str| row.get(key).and_then(Value::as_str);
+    vec![
+        str_field("resolved_package_id").unwrap_or("").to_string(),
+        (row.get("ok") == Some(&Value::Bool(true))).to_string(),
+        row.get("module_names")
+            .and_then(Value::as_array)
+            .map(|names| names.len().to_string())
+            .unwrap_or_default(),
+        stackless_error_text(row)
+            .or_else(|| str_field("error").map(str::to_string))
+            .or_else(|| str_field("skipped").map(str::to_string))
+            .unwrap_or_default(),
+    ]
+}
 
-    let mut out = Vec::new();
-    for (prefix, suffix62) in entries.into_iter().take(limit) {
-        let prefix_hex = prefix.strip_prefix("0x").unwrap_or(prefix.as_str());
-        out.push(format!("0x{prefix_hex}{suffix62}"));
+/// Append one batch summary row in the selected format.
+fn write_summary_row<W: Write>(
+    out: &std::sync::Mutex<W>,
+    row: &Value,
+    format: SummaryFormat,
+) -> Result<()> {
+    match format {
+        SummaryFormat::Jsonl => write_jsonl_row(out, row),
+        SummaryFormat::Csv => {
+            let mut out = out.lock().expect("summary writer lock");
+            write_csv_line(&mut *out, &batch_summary_csv_values(row))?;
+            Ok(())
+        }
     }
-    Ok(out)
 }
 
-fn object_id_from_hex_str(id: &str) -> anyhow::Result<ObjectID> {
-    ObjectID::from_str(id).with_context(|| format!("invalid object id: {id}"))
+/// Run local extraction for one package and shape the result as a batch summary row.
+#[tracing::instrument(name = "package", skip_all, fields(%package_id))]
+async fn batch_summary_row(
+    client: Arc<RpcClient>,
+    opts: &LocalRunOptions,
+    package_id: String,
+    embed_sigs: bool,
+) -> Value {
+    match run_single_local_sui_packages_with_rpc_deps(client, opts, &package_id, embed_sigs).await {
+        Ok(v) => {
+            let mut row = serde_json::json!({
+                "resolved_package_id": package_id,
+                "ok": v.stackless_error.is_none(),
+                "module_names": v.module_names,
+                "version": v.version,
+                "stackless_error": v.stackless_error,
+                "stackless_failed_modules": v.stackless_failed_modules,
+            });
+            if let Some(sigs) = v.function_sigs {
+                row["function_sigs"] = json!(sigs);
+            }
+            row
+        }
+        Err(e) => serde_json::json!({
+            "resolved_package_id": package_id,
+            "ok": false,
+            "error": format!("{e:#}"),
+        }),
+    }
 }
 
-async fn run_single_local_sui_packages_with_rpc_deps(
-    client: Arc<sui_sdk::SuiClient>,
-    package_id: &str,
-) -> Result<LocalBytecodeModuleList> {
-    let artifact_dir = sui_packages_artifact_dir_for_package_id(package_id)?;
-    let resolved = artifact_dir
-        .canonicalize()
-        .with_context(|| format!("canonicalize {}", artifact_dir.display()))?;
+/// `by_function_sig.json` key of a signature: whitespace removed, so lookups are a single
+/// map get whatever spacing the query uses.
+fn squash_function_sig(sig: &str) -> String {
+    sig.split_whitespace().collect()
+}
 
-    let bytecode_modules_dir = resolved.join("bytecode_modules");
-    let module_names = extract_module_names_from_bytecode_modules_dir(&bytecode_modules_dir)?;
-
-    let compiled_modules =
-        load_compiled_modules_with_rpc_deps(Arc::clone(&client), package_id).await?;
-    let mut stackless_error: Option<String> = None;
-    let prev_hook = std::panic::take_hook();
-    std::panic::set_hook(Box::new(|_| {}));
-    let stackless_summary = match std::panic::catch_unwind(|| {
-        from_compiled_modules(compiled_modules, /* optimize */ true)
-    }) {
-        Ok(Ok((_model, stackless, stats))) => Some(count_stackless_with_stats(&stackless, stats)),
-        Ok(Err(e)) => {
-            stackless_error = Some(format!("error: {e:#}"));
-            None
-        }
-        Err(panic_payload) => {
-            let msg = if let Some(s) = panic_payload.downcast_ref::<&str>() {
-                s.to_string()
-            } else if let Some(s) = panic_payload.downcast_ref::<String>() {
-                s.clone()
-            } else {
-                "<non-string panic>".to_string()
-            };
-            stackless_error = Some(format!("panic: {msg}"));
-            None
+/// `FunctionInv::signature` of each function in `modules`, keyed by `module::function`.
+fn function_signatures(
+    modules: &[impl std::borrow::Borrow<CompiledModule>],
+    inventory: &InventoryOptions,
+) -> BTreeMap<String, String> {
+    package_inventory_from_compiled_modules(modules, inventory)
+        .modules
+        .iter()
+        .flat_map(|(module, m)| {
+            m.functions
+                .iter()
+                .map(move |(name, f)| (format!("{}::{}", module, name), f.signature()))
+        })
+        .collect()
+}
+
+/// Total size of a dataset package's `.mv` files; `None` when it has no local artifacts.
+fn local_module_bytes(dataset: &DatasetOptions, package_id: &str) -> Result<Option<u64>> {
+    let artifact_dir =
+        match sui_packages_artifact_dir_for_package_id(dataset, package_id)?.canonicalize() {
+            Ok(d) => d,
+            Err(_) => return Ok(None),
+        };
+    let bytecode_modules_dir = artifact_dir.join("bytecode_modules");
+    let mut total = 0u64;
+    for entry in fs::read_dir(&bytecode_modules_dir)
+        .with_context(|| format!("read_dir {}", bytecode_modules_dir.display()))?
+    {
+        let entry = entry?;
+        if entry.path().extension().and_then(|s| s.to_str()) == Some("mv") {
+            total += entry.metadata()?.len();
         }
-    };
-    std::panic::set_hook(prev_hook);
+    }
+    Ok(Some(total))
+}
 
-    Ok(LocalBytecodeModuleList {
-        package_id: package_id.to_string(),
-        dataset: "sui-packages/mainnet_most_used".to_string(),
-        resolved_artifact_dir: resolved.display().to_string(),
-        resolved_bytecode_modules_dir: bytecode_modules_dir.display().to_string(),
-        module_names,
-        stackless_summary,
-        stackless_error,
-    })
+/// Skip reason when `limit` is set and the package's local modules exceed it. Packages
+/// whose size can't be determined are not skipped.
+fn module_bytes_skip_reason(
+    dataset: &DatasetOptions,
+    package_id: &str,
+    limit: Option<u64>,
+) -> Option<String> {
+    let limit = limit?;
+    match local_module_bytes(dataset, package_id) {
+        Ok(Some(bytes)) if bytes > limit => {
+            Some(format!("module_bytes_over_limit: {} > {}", bytes, limit))
+        }
+        _ => None,
+    }
 }
 
+#[tracing::instrument(name = "package", skip_all, fields(package_id = %package_id_str))]
 async fn verify_one_package_inventory(
-    client: Arc<sui_sdk::SuiClient>,
+    client: Arc<RpcClient>,
     package_id_str: &str,
+    opts: &VerifyOptions,
 ) -> InventoryVerifyRow {
     let mut row = InventoryVerifyRow {
         resolved_package_id: package_id_str.to_string(),
+        version: None,
         ok: false,
         error: None,
         modules_missing_local: vec![],
         modules_missing_rpc: vec![],
         modules_with_diffs: vec![],
         diff_summary: BTreeMap::new(),
+        diff_severity: BTreeMap::new(),
+        breaking: false,
+        diffs: vec![],
+        diffs_truncated: false,
+        source_digest_mismatch: false,
+        normalized_field_names: vec![],
+        upgrade_policy: None,
+        struct_diffs: BTreeMap::new(),
+        skipped: None,
+        deps_truncated: vec![],
+        dependency_cycle: vec![],
+        skipped_modules: 0,
+        sample_seed: None,
+        text_report: BTreeMap::new(),
     };
 
+    if let Some(reason) = module_bytes_skip_reason(
+        &opts.dataset,
+        package_id_str,
+        opts.skip_if_module_bytes_over,
+    ) {
+        row.skipped = Some(reason);
+        return row;
+    }
+
     let rpc_oid = match object_id_from_hex_str(package_id_str) {
         Ok(v) => v,
         Err(e) => {
-            row.error = Some(format!("invalid_object_id: {e:#}"));
+            row.error = Some(VerifyError::InvalidObjectId(format!("{e:#}")));
             return row;
         }
     };
 
-    let rpc_modules = match client
-        .read_api()
-        .get_normalized_move_modules_by_package(rpc_oid)
-        .await
-    {
-        Ok(v) => v,
-        Err(e) => {
-            row.error = Some(format!("rpc_normalized_modules_error: {e:#}"));
-            return row;
-        }
-    };
-    let mut rpc_modules_value = match serde_json::to_value(&rpc_modules) {
-        Ok(v) => v,
-        Err(e) => {
-            row.error = Some(format!("rpc_normalized_modules_serialize_error: {e:#}"));
-            return row;
-        }
-    };
-    canonicalize_json_value(&mut rpc_modules_value);
+    // The RPC normalized-modules fetch and the local load are independent, so run them
+    // concurrently. Errors are still reported RPC-first, as when they ran sequentially.
+    let (rpc_modules, local_compiled) = tokio::join!(
+        client.packages().normalized_modules(rpc_oid),
+        load_compiled_modules_with_rpc_deps(Arc::clone(&client), &opts.dataset, package_id_str),
+    );
 
-    let rpc_inv = match package_inventory_from_normalized_modules(&rpc_modules_value) {
+    let mut rpc_modules_value = match rpc_modules {
         Ok(v) => v,
         Err(e) => {
-            row.error = Some(format!("rpc_inventory_parse_error: {e:#}"));
+            row.error = Some(VerifyError::from_rpc(&e, VerifyError::RpcNormalizedModules));
             return row;
         }
     };
+    canonicalize_json_value(&mut rpc_modules_value);
 
-    let local_compiled =
-        match load_compiled_modules_with_rpc_deps(Arc::clone(&client), package_id_str).await {
+    let mut rpc_inv =
+        match package_inventory_from_normalized_modules(&rpc_modules_value, &opts.inventory) {
             Ok(v) => v,
             Err(e) => {
-                row.error = Some(format!("local_compiled_modules_error: {e:#}"));
+                row.error = Some(VerifyError::RpcInventoryParse(format!("{e:#}")));
                 return row;
             }
         };
+    // An empty map means the RPC had nothing for this package (system or malformed
+    // packages); diffing against it would just report every local module as missing.
+    if rpc_inv.modules.is_empty() {
+        row.error = Some(VerifyError::RpcReturnedNoModules);
+        return row;
+    }
+
+    let local_compiled = match local_compiled {
+        Ok(v) => {
+            row.deps_truncated = v.truncated_deps.into_iter().collect();
+            row.dependency_cycle = v.dependency_cycle;
+            row.skipped_modules = v.skipped_modules;
+            row.version = v.version;
+            v.modules
+        }
+        Err(e) if e.is::<DependencyCycle>() => {
+            row.error = Some(VerifyError::DependencyCycle(format!("{e:#}")));
+            return row;
+        }
+        Err(e) => {
+            row.error = Some(VerifyError::from_rpc(&e, VerifyError::LocalModulesLoad));
+            return row;
+        }
+    };
 
     let addrs_before: std::collections::BTreeSet<String> = local_compiled
         .iter()
@@ -1299,55 +2656,165 @@ async fn verify_one_package_inventory(
         .collect();
 
     // For upgraded packages, module bytecode still embeds the original package address.
-    let original_id = read_original_package_id_from_metadata(package_id_str)
-        .unwrap_or_else(|_| package_id_str.to_string());
+    let original_id = resolve_original_package_id(&opts.dataset, package_id_str);
     let package_addr = {
         let hex = original_id.strip_prefix("0x").unwrap_or(&original_id);
         let padded = format!("{:0>64}", hex);
         move_core_types::account_address::AccountAddress::from_hex_literal(&format!("0x{}", padded))
             .unwrap_or_else(|_| move_core_types::account_address::AccountAddress::from(rpc_oid))
     };
-    let local_compiled: Vec<CompiledModule> = local_compiled
+    let local_compiled: Vec<Arc<CompiledModule>> = local_compiled
         .into_iter()
         .filter(|m| *m.self_id().address() == package_addr)
         .collect();
 
     if local_compiled.is_empty() {
-        row.error = Some(format!(
-            "local_package_modules_not_found: package_addr={}, addrs_before={:?}",
+        row.error = Some(VerifyError::LocalPackageNotFound(format!(
+            "package_addr={}, addrs_before={:?}",
             package_addr.to_hex_literal(),
             addrs_before
-        ));
+        )));
         return row;
     }
 
-    let local_inv = package_inventory_from_compiled_modules(&local_compiled);
+    let mut local_inv = package_inventory_from_compiled_modules(&local_compiled, &opts.inventory);
 
-    for m in rpc_inv.modules.keys() {
-        if !local_inv.modules.contains_key(m) {
-            row.modules_missing_local.push(m.clone());
+    if let Some(renames) = opts
+        .module_renames
+        .get(&normalize_package_id(package_id_str))
+    {
+        rename_inventory_modules(&mut local_inv, renames);
+    }
+
+    if let Some(allowed) = opts
+        .modules_allowlist
+        .get(&normalize_package_id(package_id_str))
+    {
+        rpc_inv.modules.retain(|m, _| allowed.contains(m));
+        local_inv.modules.retain(|m, _| allowed.contains(m));
+    }
+
+    if opts.normalize_field_names {
+        for (side, inv) in [("local", &mut local_inv), ("rpc", &mut rpc_inv)] {
+            for renamed in normalize_struct_field_names(inv) {
+                row.normalized_field_names
+                    .push(format!("{}: {}", side, renamed));
+            }
         }
     }
-    for m in local_inv.modules.keys() {
-        if !rpc_inv.modules.contains_key(m) {
-            row.modules_missing_rpc.push(m.clone());
+
+    if opts.sort_fields {
+        sort_struct_fields(&mut local_inv);
+        sort_struct_fields(&mut rpc_inv);
+    }
+
+    if opts.ignore_self_address {
+        let self_addrs: BTreeSet<String> = [package_id_str, original_id.as_str()]
+            .into_iter()
+            .map(str::to_string)
+            .chain(
+                local_inv
+                    .modules
+                    .values()
+                    .chain(rpc_inv.modules.values())
+                    .filter_map(|m| m.module_address.clone()),
+            )
+            .map(|a| normalize_package_id(&a))
+            .collect();
+        rewrite_self_addresses(&mut local_inv, &self_addrs);
+        rewrite_self_addresses(&mut rpc_inv, &self_addrs);
+    }
+
+    let pkg_diff = diff_package_inventory(&local_inv, &rpc_inv, opts.check_module_address);
+    row.breaking = pkg_diff.is_breaking();
+    row.diff_severity = pkg_diff.category_severity();
+    row.modules_missing_local = pkg_diff.modules_missing_self;
+    row.modules_missing_rpc = pkg_diff.modules_missing_other;
+    row.modules_with_diffs = pkg_diff.modules_with_diffs;
+    row.diff_summary = pkg_diff.diff_summary;
+    row.diffs = pkg_diff.diffs;
+    row.struct_diffs = pkg_diff.struct_diffs;
+    if !row.diffs.is_empty() || !row.modules_with_diffs.is_empty() {
+        tracing::info!(
+            modules_with_diffs = row.modules_with_diffs.len(),
+            diffs = row.diff_summary.values().sum::<usize>(),
+            "inventory differs"
+        );
+    }
+
+    if opts.text_report {
+        for m in &row.modules_missing_local {
+            row.text_report
+                .entry(m.clone())
+                .or_default()
+                .push("missing locally".to_string());
+        }
+        for m in &row.modules_missing_rpc {
+            row.text_report
+                .entry(m.clone())
+                .or_default()
+                .push("missing on rpc".to_string());
+        }
+        for m in &row.modules_with_diffs {
+            if let (Some(l), Some(r)) = (local_inv.modules.get(m), rpc_inv.modules.get(m)) {
+                let mut lines = describe_module_inventory_diff(l, r);
+                if opts.check_module_address && module_addresses_differ(l, r) {
+                    lines.push(format!(
+                        "address differs (local {:?} vs rpc {:?})",
+                        l.module_address, r.module_address
+                    ));
+                }
+                row.text_report.insert(m.clone(), lines);
+            }
         }
     }
 
-    for (mname, rpc_m) in &rpc_inv.modules {
-        let Some(local_m) = local_inv.modules.get(mname) else {
-            continue;
-        };
-        let (ok, diffs) = diff_module_inventory(local_m, rpc_m);
-        if !ok {
-            row.modules_with_diffs.push(mname.clone());
-            for (k, v) in diffs {
-                *row.diff_summary.entry(k).or_default() += v;
+    if let Some(max) = opts.max_diffs_per_package {
+        if row.diffs.len() > max {
+            row.diffs.truncate(max);
+            row.diffs_truncated = true;
+        }
+    }
+
+    // Only flag when both sides publish a digest; absence on either side is not a mismatch.
+    if opts.check_source_digest {
+        let local_digest =
+            read_metadata_string_field(&opts.dataset, package_id_str, "sourceDigest")
+                .ok()
+                .flatten();
+        if let Some(local_digest) = local_digest {
+            match fetch_source_digest_via_rpc(Arc::clone(&client), rpc_oid).await {
+                Ok(Some(onchain_digest)) if onchain_digest != local_digest => {
+                    row.source_digest_mismatch = true;
+                    *row.diff_summary
+                        .entry("source_digest_mismatch".to_string())
+                        .or_default() += 1;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    row.error = Some(VerifyError::from_rpc(&e, VerifyError::RpcSourceDigest));
+                    return row;
+                }
             }
         }
     }
 
+    // Context only: a failed lookup leaves `upgrade_policy` unset rather than failing the row.
+    if opts.fetch_upgrade_policy {
+        match fetch_upgrade_policy_via_rpc(Arc::clone(&client), rpc_oid).await {
+            Ok(policy) => row.upgrade_policy = policy,
+            Err(e) => tracing::warn!(error = %format!("{e:#}"), "upgrade policy lookup failed"),
+        }
+    }
+
+    for c in row.diff_summary.keys() {
+        row.diff_severity
+            .entry(c.clone())
+            .or_insert_with(|| Severity::of_category(c));
+    }
+
     row.ok = row.error.is_none()
+        && !row.source_digest_mismatch
         && row.modules_missing_local.is_empty()
         && row.modules_missing_rpc.is_empty()
         && row.modules_with_diffs.is_empty();
@@ -1355,11 +2822,15 @@ async fn verify_one_package_inventory(
 }
 
 /// Verify one package and return detailed corpus report matching extractor1 schema
+#[tracing::instrument(name = "package", skip_all, fields(package_id = %package_id_str))]
 async fn verify_one_package_corpus(
-    client: Arc<sui_sdk::SuiClient>,
+    client: Arc<RpcClient>,
+    dataset: &DatasetOptions,
+    inventory: &InventoryOptions,
     package_id_str: &str,
+    sort_fields: bool,
 ) -> CorpusReportRow {
-    let package_dir = sui_packages_artifact_dir_for_package_id(package_id_str)
+    let package_dir = sui_packages_artifact_dir_for_package_id(dataset, package_id_str)
         .and_then(|p| p.canonicalize().map_err(|e| anyhow!(e)))
         .map(|p| p.display().to_string())
         .unwrap_or_default();
@@ -1376,22 +2847,27 @@ async fn verify_one_package_corpus(
     };
 
     // Load local compiled modules
-    let local_compiled =
-        match load_compiled_modules_with_rpc_deps(Arc::clone(&client), package_id_str).await {
-            Ok(v) => v,
+    let (local_compiled, skipped_modules) =
+        match load_compiled_modules_with_rpc_deps(Arc::clone(&client), dataset, package_id_str)
+            .await
+        {
+            Ok(v) => (v.modules, v.skipped_modules),
+            Err(e) if e.is::<DependencyCycle>() => {
+                row.error = Some(VerifyError::DependencyCycle(format!("{e:#}")));
+                return row;
+            }
             Err(e) => {
-                row.error = Some(format!("local_compiled_modules_error: {e:#}"));
+                row.error = Some(VerifyError::from_rpc(&e, VerifyError::LocalModulesLoad));
                 return row;
             }
         };
 
     // Filter to just this package's modules (not dependencies)
-    let original_id = read_original_package_id_from_metadata(package_id_str)
-        .unwrap_or_else(|_| package_id_str.to_string());
+    let original_id = resolve_original_package_id(dataset, package_id_str);
     let rpc_oid = match object_id_from_hex_str(package_id_str) {
         Ok(v) => v,
         Err(e) => {
-            row.error = Some(format!("invalid_object_id: {e:#}"));
+            row.error = Some(VerifyError::InvalidObjectId(format!("{e:#}")));
             return row;
         }
     };
@@ -1401,49 +2877,45 @@ async fn verify_one_package_corpus(
         move_core_types::account_address::AccountAddress::from_hex_literal(&format!("0x{}", padded))
             .unwrap_or_else(|_| move_core_types::account_address::AccountAddress::from(rpc_oid))
     };
-    let local_compiled: Vec<CompiledModule> = local_compiled
+    let local_compiled: Vec<Arc<CompiledModule>> = local_compiled
         .into_iter()
         .filter(|m| *m.self_id().address() == package_addr)
         .collect();
 
     if local_compiled.is_empty() {
-        row.error = Some("local_package_modules_not_found".to_string());
+        row.error = Some(VerifyError::LocalPackageNotFound(String::new()));
         return row;
     }
 
     // Compute local stats
     row.local = compute_local_stats(&local_compiled);
-    let local_inv = package_inventory_from_compiled_modules(&local_compiled);
+    row.local.skipped_modules = skipped_modules;
+    let mut local_inv = package_inventory_from_compiled_modules(&local_compiled, inventory);
 
     // Fetch RPC normalized modules
-    let rpc_modules = match client
-        .read_api()
-        .get_normalized_move_modules_by_package(rpc_oid)
-        .await
-    {
-        Ok(v) => v,
-        Err(e) => {
-            row.error = Some(format!("rpc_normalized_modules_error: {e:#}"));
-            return row;
-        }
-    };
-
-    let mut rpc_modules_value = match serde_json::to_value(&rpc_modules) {
+    let mut rpc_modules_value = match client.packages().normalized_modules(rpc_oid).await {
         Ok(v) => v,
         Err(e) => {
-            row.error = Some(format!("rpc_serialize_error: {e:#}"));
+            row.error = Some(VerifyError::from_rpc(&e, VerifyError::RpcNormalizedModules));
             return row;
         }
     };
     canonicalize_json_value(&mut rpc_modules_value);
 
-    let rpc_inv = match package_inventory_from_normalized_modules(&rpc_modules_value) {
+    let mut rpc_inv = match package_inventory_from_normalized_modules(&rpc_modules_value, inventory)
+    {
         Ok(v) => v,
         Err(e) => {
-            row.error = Some(format!("rpc_inventory_parse_error: {e:#}"));
+            row.error = Some(VerifyError::RpcInventoryParse(format!("{e:#}")));
             return row;
         }
     };
+    // An empty map means the RPC had nothing for this package (system or malformed
+    // packages); diffing against it would just report every local module as missing.
+    if rpc_inv.modules.is_empty() {
+        row.error = Some(VerifyError::RpcReturnedNoModules);
+        return row;
+    }
 
     // Compute RPC stats
     row.rpc = compute_rpc_stats(&rpc_inv);
@@ -1462,6 +2934,11 @@ async fn verify_one_package_corpus(
         }
     }
 
+    if sort_fields {
+        sort_struct_fields(&mut local_inv);
+        sort_struct_fields(&mut rpc_inv);
+    }
+
     // Compute interface comparison
     let mut modules_compared = 0usize;
     let mut structs_compared = 0usize;
@@ -1523,16 +3000,372 @@ struct CorpusSummary {
     interface_mismatch_packages: usize,
     interface_mismatches_total: usize,
     problems: usize,
+    /// `--verify-sample-seed` the packages were sampled with.
+    sample_seed: Option<u64>,
     report_jsonl: String,
     index_jsonl: String,
     problems_jsonl: String,
 }
 
+/// Columns available for `--verify-inventory-out-csv`, in default order.
+const VERIFY_CSV_FIELDS: [&str; 12] = [
+    "resolved_package_id",
+    "ok",
+    "error",
+    "modules_missing_local",
+    "modules_missing_rpc",
+    "modules_with_diffs",
+    "diff_count",
+    "diffs_truncated",
+    "upgrade_policy",
+    "deps_truncated",
+    "skipped_modules",
+    "version",
+];
+
+/// Quote a CSV field when it contains a delimiter, quote, or line break (RFC 4180).
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_csv_line(out: &mut impl Write, values: &[String]) -> std::io::Result<()> {
+    let line = values
+        .iter()
+        .map(|v| csv_escape(v))
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(out, "{}", line)
+}
+
+/// Resolve `--output-fields` against the available columns, erroring on unknown names.
+fn select_csv_fields(requested: &[String], available: &[&str]) -> Result<Vec<String>> {
+    if requested.is_empty() {
+        return Ok(available.iter().map(|f| f.to_string()).collect());
+    }
+    let unknown: Vec<&str> = requested
+        .iter()
+        .map(String::as_str)
+        .filter(|f| !available.contains(f))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(anyhow!(
+            "unknown --output-fields {:?} (available: {})",
+            unknown,
+            available.join(",")
+        ));
+    }
+    Ok(requested.to_vec())
+}
+
+fn verify_row_csv_value(row: &InventoryVerifyRow, field: &str) -> String {
+    match field {
+        "resolved_package_id" => row.resolved_package_id.clone(),
+        "ok" => row.ok.to_string(),
+        "error" => row
+            .error
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default(),
+        "modules_missing_local" => row.modules_missing_local.join(";"),
+        "modules_missing_rpc" => row.modules_missing_rpc.join(";"),
+        "modules_with_diffs" => row.modules_with_diffs.join(";"),
+        "deps_truncated" => row.deps_truncated.join(";"),
+        "skipped_modules" => row.skipped_modules.to_string(),
+        "version" => row.version.map(|v| v.to_string()).unwrap_or_default(),
+        "diff_count" => row.diff_summary.values().sum::<usize>().to_string(),
+        "diffs_truncated" => row.diffs_truncated.to_string(),
+        "upgrade_policy" => row.upgrade_policy.clone().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+const VERIFY_SQLITE_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS verify_results (
+    resolved_package_id TEXT PRIMARY KEY,
+    ok INTEGER NOT NULL,
+    error TEXT,
+    skipped TEXT,
+    diff_count INTEGER NOT NULL,
+    diffs_truncated INTEGER NOT NULL,
+    source_digest_mismatch INTEGER NOT NULL,
+    upgrade_policy TEXT,
+    modules_missing_local TEXT NOT NULL,
+    modules_missing_rpc TEXT NOT NULL,
+    modules_with_diffs TEXT NOT NULL,
+    diff_summary TEXT NOT NULL,
+    diffs TEXT NOT NULL,
+    struct_diffs TEXT NOT NULL
+)";
+
+/// One destination for verify rows. `run_verify_inventory` opens every requested sink
+/// before verifying, so a bad output path fails the run up front, then feeds each row to
+/// all of them.
+trait OutputSink {
+    fn write_row(&mut self, row: &InventoryVerifyRow) -> Result<()>;
+
+    /// Flush and move the output into place.
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+/// `--verify-inventory-out-jsonl`: one full row (or `--modules-summary-only` row) per line.
+struct JsonlSink {
+    path: PathBuf,
+    out: std::io::BufWriter<fs::File>,
+    summary_only: bool,
+}
+
+impl JsonlSink {
+    fn create(path: &Path, summary_only: bool) -> Result<Self> {
+        let file = create_partial_output(path).with_context(|| {
+            format!(
+                "failed to create inventory verify jsonl: {}",
+                path.display()
+            )
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            out: std::io::BufWriter::new(file),
+            summary_only,
+        })
+    }
+}
+
+impl OutputSink for JsonlSink {
+    fn write_row(&mut self, row: &InventoryVerifyRow) -> Result<()> {
+        if self.summary_only {
+            serde_json::to_writer(&mut self.out, &InventoryVerifySummaryRow::from(row))?;
+        } else {
+            serde_json::to_writer(&mut self.out, row)?;
+        }
+        self.out.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.out.flush()?;
+        commit_partial_output(&self.path)
+    }
+}
+
+/// `--verify-inventory-out-csv`: the `--output-fields` columns. With `--csv-no-header` rows
+/// are appended to the file in place.
+struct CsvSink {
+    path: PathBuf,
+    out: std::io::BufWriter<fs::File>,
+    fields: Vec<String>,
+    no_header: bool,
+}
+
+impl CsvSink {
+    fn create(path: &Path, fields: Vec<String>, no_header: bool) -> Result<Self> {
+        let file = if no_header {
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(anyhow::Error::from)
+        } else {
+            create_partial_output(path)
+        }
+        .with_context(|| format!("failed to open csv: {}", path.display()))?;
+        let mut out = std::io::BufWriter::new(file);
+        if !no_header {
+            write_csv_line(&mut out, &fields)?;
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            out,
+            fields,
+            no_header,
+        })
+    }
+}
+
+impl OutputSink for CsvSink {
+    fn write_row(&mut self, row: &InventoryVerifyRow) -> Result<()> {
+        let values: Vec<String> = self
+            .fields
+            .iter()
+            .map(|f| verify_row_csv_value(row, f))
+            .collect();
+        write_csv_line(&mut self.out, &values)?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.out.flush()?;
+        if !self.no_header {
+            commit_partial_output(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// `--verify-out-sqlite`: upserts into `verify_results`, scalars as columns and lists and
+/// maps as JSON text, in one transaction committed by `finish`.
+struct SqliteSink {
+    conn: rusqlite::Connection,
+}
+
+impl SqliteSink {
+    fn open(path: &Path) -> Result<Self> {
+        let conn = rusqlite::Connection::open(path)
+            .with_context(|| format!("failed to open sqlite db: {}", path.display()))?;
+        conn.execute_batch(VERIFY_SQLITE_SCHEMA)?;
+        conn.execute_batch("BEGIN")?;
+        Ok(Self { conn })
+    }
+}
+
+impl OutputSink for SqliteSink {
+    fn write_row(&mut self, row: &InventoryVerifyRow) -> Result<()> {
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT OR REPLACE INTO verify_results (
+                resolved_package_id, ok, error, skipped, diff_count, diffs_truncated,
+                source_digest_mismatch, upgrade_policy, modules_missing_local,
+                modules_missing_rpc, modules_with_diffs, diff_summary, diffs, struct_diffs
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        )?;
+        stmt.execute(rusqlite::params![
+            row.resolved_package_id,
+            row.ok,
+            row.error.as_ref().map(ToString::to_string),
+            row.skipped,
+            row.diff_summary.values().sum::<usize>() as i64,
+            row.diffs_truncated,
+            row.source_digest_mismatch,
+            row.upgrade_policy,
+            serde_json::to_string(&row.modules_missing_local)?,
+            serde_json::to_string(&row.modules_missing_rpc)?,
+            serde_json::to_string(&row.modules_with_diffs)?,
+            serde_json::to_string(&row.diff_summary)?,
+            serde_json::to_string(&row.diffs)?,
+            serde_json::to_string(&row.struct_diffs)?,
+        ])?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        self.conn.execute_batch("COMMIT")?;
+        Ok(())
+    }
+}
+
+fn print_verify_text_report(rows: &[InventoryVerifyRow]) {
+    for row in rows.iter().filter(|r| !r.ok) {
+        println!("package {}:", row.resolved_package_id);
+        if let Some(ref err) = row.error {
+            println!("  error: {}", err);
+        }
+        for (module, lines) in &row.text_report {
+            for line in lines {
+                println!("  module {}: {}", module, line);
+            }
+        }
+    }
+}
+
+/// `--verify-report-md` document: a summary table of every package, then a section per
+/// failing package with its errors, missing modules and diff category counts. Packages are
+/// sorted by id so reruns diff cleanly.
+fn render_verify_markdown(rows: &[InventoryVerifyRow]) -> String {
+    fn code_list(items: &[String]) -> String {
+        items
+            .iter()
+            .map(|m| format!("`{}`", m))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    let mut sorted: Vec<&InventoryVerifyRow> = rows.iter().collect();
+    sorted.sort_by(|a, b| a.resolved_package_id.cmp(&b.resolved_package_id));
+    let ok = sorted.iter().filter(|r| r.ok).count();
+
+    let mut out = String::from("# Inventory verification\n\n");
+    out.push_str(&format!(
+        "{} packages: {} ok, {} failed.\n\n",
+        sorted.len(),
+        ok,
+        sorted.len() - ok
+    ));
+    out.push_str("| Package | Status | Modules with diffs |\n|---|---|---|\n");
+    for row in &sorted {
+        out.push_str(&format!(
+            "| `{}` | {} | {} |\n",
+            row.resolved_package_id,
+            if row.ok { "ok" } else { "fail" },
+            code_list(&row.modules_with_diffs)
+        ));
+    }
+
+    for row in sorted.iter().filter(|r| !r.ok) {
+        out.push_str(&format!("\n## `{}`\n\n", row.resolved_package_id));
+        if let Some(ref err) = row.error {
+            out.push_str(&format!(
+                "- error: {}\n",
+                err.to_string().replace(['\n', '\r'], " ")
+            ));
+        }
+        if !row.modules_missing_local.is_empty() {
+            out.push_str(&format!(
+                "- missing locally: {}\n",
+                code_list(&row.modules_missing_local)
+            ));
+        }
+        if !row.modules_missing_rpc.is_empty() {
+            out.push_str(&format!(
+                "- missing on RPC: {}\n",
+                code_list(&row.modules_missing_rpc)
+            ));
+        }
+        if !row.diff_summary.is_empty() {
+            out.push_str("\n| Category | Count |\n|---|---|\n");
+            for (category, count) in &row.diff_summary {
+                out.push_str(&format!("| {} | {} |\n", category, count));
+            }
+        }
+    }
+    out
+}
+
+/// Re-run the packages whose rows errored, replacing each row with its retry result.
+///
+/// `errored` returns the package id of a row that should be retried. Stops early once a
+/// pass leaves no errored rows.
+async fn retry_errored_rows<R, Fut>(
+    rows: &mut [R],
+    passes: usize,
+    errored: impl Fn(&R) -> Option<String>,
+    run: impl Fn(String) -> Fut,
+) where
+    Fut: std::future::Future<Output = R>,
+{
+    for pass in 1..=passes {
+        let failed: Vec<(usize, String)> = rows
+            .iter()
+            .enumerate()
+            .filter_map(|(i, r)| errored(r).map(|id| (i, id)))
+            .collect();
+        if failed.is_empty() {
+            break;
+        }
+        tracing::info!(pass, errored = failed.len(), "retry pass");
+        for (i, package_id) in failed {
+            rows[i] = run(package_id).await;
+        }
+    }
+}
+
 async fn run_verify_inventory(
     args: &Args,
-    client: Arc<sui_sdk::SuiClient>,
+    client: Arc<RpcClient>,
     summary_jsonl_path: &std::path::Path,
 ) -> Result<PathBuf> {
+    let csv_fields = select_csv_fields(&args.output_fields, &VERIFY_CSV_FIELDS)?;
+
     let ids = read_package_ids_from_summary_jsonl(summary_jsonl_path)?;
     if ids.is_empty() {
         return Err(anyhow!(
@@ -1541,39 +3374,267 @@ async fn run_verify_inventory(
         ));
     }
 
-    let sample_size = args
-        .verify_inventory_sample_size
-        .unwrap_or(ids.len())
-        .min(ids.len());
-    let selected = &ids[..sample_size];
+    let selected = &select_verify_sample(
+        ids,
+        args.verify_sample_seed,
+        args.verify_inventory_sample_size,
+    );
 
     let out_path = args
         .verify_inventory_out_jsonl
         .clone()
         .unwrap_or_else(|| PathBuf::from("/tmp/bytecode_move_model2_verify_inventory.jsonl"));
 
-    let out_file = std::fs::File::create(&out_path).with_context(|| {
-        format!(
-            "failed to create inventory verify jsonl: {}",
-            out_path.display()
+    let mut sinks: Vec<(String, Box<dyn OutputSink>)> = Vec::new();
+    sinks.push((
+        String::new(),
+        Box::new(JsonlSink::create(&out_path, args.modules_summary_only)?),
+    ));
+    if let Some(ref csv_path) = args.verify_inventory_out_csv {
+        sinks.push((
+            format!("inventory verify csv -> {}", csv_path.display()),
+            Box::new(CsvSink::create(csv_path, csv_fields, args.csv_no_header)?),
+        ));
+    }
+    if let Some(ref sqlite_path) = args.verify_out_sqlite {
+        sinks.push((
+            format!("inventory verify sqlite -> {}", sqlite_path.display()),
+            Box::new(SqliteSink::open(sqlite_path)?),
+        ));
+    }
+
+    let opts = VerifyOptions::from_args(args)?;
+    prepare_dataset_index(&opts.dataset).await?;
+    let checkpoint = Checkpoint::new(args.checkpoint_file.clone(), selected.len());
+    // Packages are verified concurrently; rows are put back in input order afterwards so
+    // the outputs don't depend on completion order.
+    let mut indexed = Vec::with_capacity(selected.len());
+    let mut results = futures::stream::iter(selected.iter().enumerate())
+        .map(|(i, package_id)| {
+            let client = Arc::clone(&client);
+            let opts = &opts;
+            let checkpoint = &checkpoint;
+            async move {
+                checkpoint.begin(package_id);
+                let row = verify_one_package_inventory(client, package_id, opts).await;
+                checkpoint.record(row.ok);
+                (i, row)
+            }
+        })
+        .buffer_unordered(args.jobs.max(1));
+    while let Some(done) = results.next().await {
+        indexed.push(done);
+        tracing::info!("{}/{} verified", indexed.len(), selected.len());
+    }
+    checkpoint.finish();
+    indexed.sort_by_key(|(i, _)| *i);
+    let mut rows: Vec<InventoryVerifyRow> = indexed.into_iter().map(|(_, row)| row).collect();
+
+    if args.retry_failed_packages {
+        retry_errored_rows(
+            &mut rows,
+            args.retry_passes,
+            |r: &InventoryVerifyRow| r.error.as_ref().map(|_| r.resolved_package_id.clone()),
+            |id| {
+                let client = Arc::clone(&client);
+                let opts = &opts;
+                async move { verify_one_package_inventory(client, &id, opts).await }
+            },
         )
-    })?;
-    let mut out = std::io::BufWriter::new(out_file);
+        .await;
+    }
+
+    for row in &mut rows {
+        row.sample_seed = args.verify_sample_seed;
+    }
+
+    for row in &rows {
+        for (_, sink) in &mut sinks {
+            sink.write_row(row)?;
+        }
+    }
+    for (done, sink) in sinks {
+        sink.finish()?;
+        if !done.is_empty() {
+            println!("{done}");
+        }
+    }
+
+    if args.verify_report_format == VerifyReportFormat::Text {
+        print_verify_text_report(&rows);
+    }
+
+    if let Some(ref md_path) = args.verify_report_md {
+        write_file_atomic(md_path, render_verify_markdown(&rows).as_bytes())?;
+        println!("inventory verify markdown -> {}", md_path.display());
+    }
+
+    if let Some(ref categories_path) = args.emit_category_list {
+        let categories: BTreeSet<&String> =
+            rows.iter().flat_map(|r| r.diff_summary.keys()).collect();
+        write_file_atomic(categories_path, &serde_json::to_vec_pretty(&categories)?)?;
+        println!("diff categories -> {}", categories_path.display());
+    }
 
-    for package_id in selected {
-        let row = verify_one_package_inventory(Arc::clone(&client), package_id).await;
-        serde_json::to_writer(&mut out, &row)?;
-        out.write_all(b"\n")?;
+    if let Some(ref correlation_path) = args.category_correlation {
+        let correlation = category_correlation(&rows);
+        write_file_atomic(correlation_path, &serde_json::to_vec_pretty(&correlation)?)?;
+        println!("category correlation -> {}", correlation_path.display());
+    }
+
+    let aggregate = VerifyAggregate::from_rows(&rows);
+    if let Some(ref aggregate_path) = args.verify_aggregate_out {
+        write_file_atomic(aggregate_path, &serde_json::to_vec_pretty(&aggregate)?)?;
+        println!("verify aggregate -> {}", aggregate_path.display());
+    }
+    println!(
+        "verify pass rate: {}/{} ok ({:.1}%), {} failed",
+        aggregate.ok,
+        aggregate.packages,
+        aggregate.pass_rate * 100.0,
+        aggregate.failing
+    );
+
+    if args.fail_on_breaking {
+        let breaking = rows.iter().filter(|r| r.breaking).count();
+        if breaking > 0 {
+            return Err(anyhow!(
+                "breaking interface diffs in {} of {} packages",
+                breaking,
+                rows.len()
+            ));
+        }
+    }
+    if args.fail_on_diff && aggregate.failing > 0 {
+        return Err(anyhow!(
+            "inventory verification failed for {} of {} packages",
+            aggregate.failing,
+            aggregate.packages
+        ));
     }
 
-    out.flush()?;
     Ok(out_path)
 }
 
+/// Totals over a verify run's rows, written by `--verify-aggregate-out`.
+#[derive(Debug, Serialize)]
+struct VerifyAggregate {
+    packages: usize,
+    ok: usize,
+    failing: usize,
+    /// `ok / packages`, or 0 for an empty run.
+    pass_rate: f64,
+    /// Every row's `diff_summary`, summed per category.
+    diff_summary: BTreeMap<String, usize>,
+    /// Module names by the number of packages listing them in `modules_with_diffs`, most
+    /// frequent first (then by name), capped at `TOP_MODULES`.
+    top_modules_with_diffs: Vec<ModuleDiffCount>,
+}
+
+#[derive(Debug, Serialize)]
+struct ModuleDiffCount {
+    module: String,
+    packages: usize,
+}
+
+impl VerifyAggregate {
+    const TOP_MODULES: usize = 20;
+
+    fn from_rows(rows: &[InventoryVerifyRow]) -> Self {
+        let ok = rows.iter().filter(|r| r.ok).count();
+        let mut diff_summary: BTreeMap<String, usize> = BTreeMap::new();
+        let mut modules: BTreeMap<&str, usize> = BTreeMap::new();
+        for row in rows {
+            for (category, count) in &row.diff_summary {
+                *diff_summary.entry(category.clone()).or_default() += count;
+            }
+            for module in &row.modules_with_diffs {
+                *modules.entry(module.as_str()).or_default() += 1;
+            }
+        }
+        let mut top: Vec<(&str, usize)> = modules.into_iter().collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        top.truncate(Self::TOP_MODULES);
+        VerifyAggregate {
+            packages: rows.len(),
+            ok,
+            failing: rows.len() - ok,
+            pass_rate: if rows.is_empty() {
+                0.0
+            } else {
+                ok as f64 / rows.len() as f64
+            },
+            diff_summary,
+            top_modules_with_diffs: top
+                .into_iter()
+                .map(|(module, packages)| ModuleDiffCount {
+                    module: module.to_string(),
+                    packages,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Symmetric co-occurrence counts of diff categories across packages.
+#[derive(Debug, Serialize)]
+struct CategoryCorrelation {
+    packages: usize,
+    categories: Vec<String>,
+    /// `matrix[i][j]` = packages whose `diff_summary` has both `categories[i]` and
+    /// `categories[j]`; the diagonal is the per-category package count.
+    matrix: Vec<Vec<usize>>,
+}
+
+fn category_correlation(rows: &[InventoryVerifyRow]) -> CategoryCorrelation {
+    let categories: Vec<String> = rows
+        .iter()
+        .flat_map(|r| r.diff_summary.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .cloned()
+        .collect();
+    let index: BTreeMap<&str, usize> = categories
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.as_str(), i))
+        .collect();
+    let mut matrix = vec![vec![0usize; categories.len()]; categories.len()];
+    for row in rows {
+        let present: Vec<usize> = row.diff_summary.keys().map(|c| index[c.as_str()]).collect();
+        for &i in &present {
+            for &j in &present {
+                matrix[i][j] += 1;
+            }
+        }
+    }
+    CategoryCorrelation {
+        packages: rows.len(),
+        categories,
+        matrix,
+    }
+}
+
+/// The ids to verify: the first `sample_size` of `ids`, after a shuffle seeded by `seed`
+/// when one is given. The shuffle uses ChaCha8 rather than `StdRng`, whose algorithm may
+/// change between `rand` releases, so a seed keeps selecting the same sample.
+fn select_verify_sample(
+    mut ids: Vec<String>,
+    seed: Option<u64>,
+    sample_size: Option<usize>,
+) -> Vec<String> {
+    if let Some(seed) = seed {
+        ids.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+    }
+    let sample_size = sample_size.unwrap_or(ids.len()).min(ids.len());
+    ids.truncate(sample_size);
+    ids
+}
+
 /// Run corpus verification and output detailed results matching extractor1 schema
 async fn run_corpus_verification(
     args: &Args,
-    client: Arc<sui_sdk::SuiClient>,
+    client: Arc<RpcClient>,
     summary_jsonl_path: &Path,
     out_dir: &Path,
 ) -> Result<()> {
@@ -1585,11 +3646,11 @@ async fn run_corpus_verification(
         ));
     }
 
-    let sample_size = args
-        .verify_inventory_sample_size
-        .unwrap_or(ids.len())
-        .min(ids.len());
-    let selected = &ids[..sample_size];
+    let selected = &select_verify_sample(
+        ids,
+        args.verify_sample_seed,
+        args.verify_inventory_sample_size,
+    );
 
     // Create output directory
     fs::create_dir_all(out_dir)?;
@@ -1599,13 +3660,13 @@ async fn run_corpus_verification(
     let problems_path = out_dir.join("problems.jsonl");
     let summary_path = out_dir.join("corpus_summary.json");
 
-    let report_file = fs::File::create(&report_path)?;
+    let report_file = create_partial_output(&report_path)?;
     let mut report_out = std::io::BufWriter::new(report_file);
 
-    let index_file = fs::File::create(&index_path)?;
+    let index_file = create_partial_output(&index_path)?;
     let mut index_out = std::io::BufWriter::new(index_file);
 
-    let problems_file = fs::File::create(&problems_path)?;
+    let problems_file = create_partial_output(&problems_path)?;
     let mut problems_out = std::io::BufWriter::new(problems_file);
 
     // Aggregate stats
@@ -1618,16 +3679,53 @@ async fn run_corpus_verification(
     let mut interface_mismatches_total = 0usize;
     let mut problems = 0usize;
 
-    for package_id in selected {
+    let dataset = args.dataset_options();
+    prepare_dataset_index(&dataset).await?;
+    let inventory = args.inventory_options();
+    let checkpoint = Checkpoint::new(args.checkpoint_file.clone(), selected.len());
+    let mut rows = Vec::with_capacity(selected.len());
+    for (i, package_id) in selected.iter().enumerate() {
+        checkpoint.begin(package_id);
+        let row = verify_one_package_corpus(
+            Arc::clone(&client),
+            &dataset,
+            &inventory,
+            package_id,
+            args.sort_fields,
+        )
+        .await;
+        checkpoint.record(row.error.is_none());
+        tracing::info!("{}/{} verified", i + 1, selected.len());
+        rows.push(row);
+    }
+    checkpoint.finish();
+
+    if args.retry_failed_packages {
+        retry_errored_rows(
+            &mut rows,
+            args.retry_passes,
+            |r: &CorpusReportRow| r.error.as_ref().map(|_| r.package_id.clone()),
+            |id| {
+                let client = Arc::clone(&client);
+                let (dataset, inventory) = (&dataset, &inventory);
+                let sort_fields = args.sort_fields;
+                async move {
+                    verify_one_package_corpus(client, dataset, inventory, &id, sort_fields).await
+                }
+            },
+        )
+        .await;
+    }
+
+    for row in &rows {
         total += 1;
-        let row = verify_one_package_corpus(Arc::clone(&client), package_id).await;
 
         // Write to report
-        serde_json::to_writer(&mut report_out, &row)?;
+        serde_json::to_writer(&mut report_out, row)?;
         report_out.write_all(b"\n")?;
 
         // Write to index
-        serde_json::to_writer(&mut index_out, &json!({"package_id": package_id}))?;
+        serde_json::to_writer(&mut index_out, &json!({"package_id": row.package_id}))?;
         index_out.write_all(b"\n")?;
 
         // Update stats
@@ -1649,7 +3747,7 @@ async fn run_corpus_verification(
             }
         } else {
             problems += 1;
-            serde_json::to_writer(&mut problems_out, &row)?;
+            serde_json::to_writer(&mut problems_out, row)?;
             problems_out.write_all(b"\n")?;
         }
     }
@@ -1657,6 +3755,9 @@ async fn run_corpus_verification(
     report_out.flush()?;
     index_out.flush()?;
     problems_out.flush()?;
+    for path in [&report_path, &index_path, &problems_path] {
+        commit_partial_output(path)?;
+    }
 
     // Write summary
     let summary = CorpusSummary {
@@ -1671,17 +3772,17 @@ async fn run_corpus_verification(
         interface_mismatch_packages,
         interface_mismatches_total,
         problems,
+        sample_seed: args.verify_sample_seed,
         report_jsonl: report_path.display().to_string(),
         index_jsonl: index_path.display().to_string(),
         problems_jsonl: problems_path.display().to_string(),
     };
 
-    let summary_file = fs::File::create(&summary_path)?;
-    serde_json::to_writer_pretty(summary_file, &summary)?;
+    write_file_atomic(&summary_path, &serde_json::to_vec_pretty(&summary)?)?;
 
     println!("corpus report -> {}", report_path.display());
     println!("corpus summary -> {}", summary_path.display());
-    println!(
+    eprintln!(
         "Results: {}/{} passed, {} problems",
         interface_ok, total, problems
     );
@@ -1692,99 +3793,774 @@ async fn run_corpus_verification(
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(args.log_level.as_filter())),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(args.jobs.max(1))
+        .build_global()
+        .context("failed to build rayon thread pool")?;
+    RPC_RETRY_POLICY
+        .set(RpcRetryPolicy {
+            max_attempts: args.rpc_max_retries + 1,
+            base_delay_ms: args.rpc_base_delay_ms,
+        })
+        .expect("rpc retry policy set once");
+    if let Some(n) = args.rpc_concurrency {
+        RPC_CONCURRENCY.set(n).expect("rpc concurrency set once");
+    }
+    RPC_TIMEOUTS
+        .set(RpcTimeouts {
+            call: Duration::from_secs(args.rpc_timeout_secs),
+            package: args.package_timeout_secs.map(Duration::from_secs),
+        })
+        .expect("rpc timeouts set once");
+    if let Some(depth) = args.max_dep_depth {
+        MAX_DEP_DEPTH.set(depth).expect("max dep depth set once");
+    }
+    FAIL_ON_DEP_CYCLE
+        .set(args.fail_on_dep_cycle)
+        .expect("fail on dep cycle set once");
+    if let Some(dir) = args.rpc_cache_dir.as_ref().filter(|_| !args.no_cache) {
+        RPC_CACHE_DIR
+            .set(dir.clone())
+            .expect("rpc cache dir set once");
+    }
 
-    let client = Arc::new(
-        sui_sdk::SuiClientBuilder::default()
-            .build(&args.rpc_url)
-            .await
-            .context("build sui client")?,
+    let network = args.resolved_dataset_network();
+    tracing::info!(
+        dataset_root = %args.dataset_options().root.display(),
+        ?network,
+        "dataset"
     );
+    let rpc_url = args
+        .rpc_url
+        .clone()
+        .unwrap_or_else(|| match (args.network, args.rpc_kind) {
+            (Some(preset), kind) => preset.rpc_url(kind).to_string(),
+            (None, RpcKind::Jsonrpc) => network.default_rpc_url().to_string(),
+            (None, RpcKind::Graphql) => network.default_graphql_url().to_string(),
+        });
+    let scheme = reqwest::Url::parse(&rpc_url)
+        .with_context(|| format!("invalid rpc url {:?}", rpc_url))?
+        .scheme()
+        .to_string();
+    if scheme != "http" && scheme != "https" {
+        return Err(anyhow!(
+            "rpc url {:?} must use http or https, not {:?}",
+            rpc_url,
+            scheme
+        ));
+    }
+
+    let result = if run_offline(&args)? {
+        Ok(())
+    } else {
+        let client = Arc::new(RpcClient::connect(args.rpc_kind, &rpc_url).await?);
+        run(&args, client).await
+    };
+    let metrics = rpc_metrics();
+    if args.print_rpc_method_timings {
+        print_rpc_method_timings(&metrics);
+    }
+    if let Some(ref path) = args.metrics_out {
+        write_file_atomic(path, &serde_json::to_vec_pretty(&metrics)?)?;
+    }
+    result
+}
+
+/// Modes that only read local files. Returns whether one of them ran, in which case no RPC
+/// client is built.
+fn run_offline(args: &Args) -> Result<bool> {
+    // Handle normalized-vs-normalized comparison mode (no RPC or bytecode involved)
+    if let Some(ref paths) = args.compare_normalized {
+        let report = compare_normalized_files(
+            &paths[0],
+            &paths[1],
+            &args.inventory_options(),
+            args.sort_fields,
+            args.check_module_address,
+        )?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(true);
+    }
+
+    // Handle summary merge mode
+    if !args.merge_summaries.is_empty() {
+        let out_path = args
+            .summary_jsonl
+            .as_ref()
+            .ok_or_else(|| anyhow!("--merge-summaries requires --summary-jsonl"))?;
+        let rows = merge_summary_jsonl_files(&args.merge_summaries)?;
+        let mut bytes = Vec::new();
+        for row in &rows {
+            serde_json::to_writer(&mut bytes, row)?;
+            bytes.push(b'\n');
+        }
+        write_file_atomic(out_path, &bytes)?;
+        println!(
+            "merged {} rows from {} files -> {}",
+            rows.len(),
+            args.merge_summaries.len(),
+            out_path.display()
+        );
+        return Ok(true);
+    }
+
+    // Handle function signature lookup against a built index
+    if let Some(ref sig) = args.query_function_sig {
+        let path = args.index_out_dir.join("by_function_sig.json");
+        let bytes =
+            fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let index: BTreeMap<String, BTreeSet<String>> = serde_json::from_slice(&bytes)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        let package_ids: BTreeSet<&String> = index
+            .get(&squash_function_sig(sig))
+            .into_iter()
+            .flatten()
+            .collect();
+        println!(
+            "{}",
+            json!({ "signature": sig, "package_ids": package_ids })
+        );
+        return Ok(true);
+    }
+
+    // Handle index-from-summary-jsonl mode
+    if let Some(ref summary_path) = args.index_from_summary_jsonl {
+        if args.index_format == IndexFormat::Sqlite {
+            if args.index_append {
+                return Err(anyhow!(
+                    "--index-append is not supported with --index-format sqlite"
+                ));
+            }
+            let db_path =
+                write_index_sqlite(summary_path, &args.index_out_dir, args.tolerate_bad_lines)?;
+            println!("index db -> {}", db_path.display());
+            return Ok(true);
+        }
+        let previous = if args.index_append && args.index_out_dir.join("meta.json").exists() {
+            let previous = read_index_artifacts(&args.index_out_dir)?;
+            if previous.meta.source_jsonl != summary_path.display().to_string() {
+                tracing::warn!(
+                    source = %previous.meta.source_jsonl,
+                    "appending to index built from a different summary"
+                );
+            }
+            Some(previous)
+        } else {
+            None
+        };
+        let index =
+            build_index_from_summary_jsonl(summary_path, previous, args.tolerate_bad_lines)?;
+        write_index_artifacts(&index, &args.index_out_dir)?;
+        println!("index artifacts -> {}", args.index_out_dir.display());
+        return Ok(true);
+    }
+
+    // Handle type definition index mode
+    if let Some(ref out_path) = args.type_definition_index {
+        let index = build_type_definition_index(
+            &args.dataset_options(),
+            args.max_packages.unwrap_or(usize::MAX),
+        )?;
+        write_file_atomic(out_path, &serde_json::to_vec_pretty(&index)?)?;
+        println!("type definition index -> {}", out_path.display());
+        return Ok(true);
+    }
+
+    // Handle parameter calling-convention stats mode
+    if let Some(ref out_path) = args.param_convention_stats {
+        let stats = build_param_convention_stats(
+            &args.dataset_options(),
+            args.max_packages.unwrap_or(usize::MAX),
+        )?;
+        write_file_atomic(out_path, &serde_json::to_vec_pretty(&stats)?)?;
+        println!("param convention stats -> {}", out_path.display());
+        return Ok(true);
+    }
+
+    // Handle duplicate package detection mode
+    if let Some(ref out_path) = args.find_duplicate_packages {
+        let groups = find_duplicate_packages(
+            &args.dataset_options(),
+            args.max_packages.unwrap_or(usize::MAX),
+        )?;
+        write_file_atomic(out_path, &serde_json::to_vec_pretty(&groups)?)?;
+        println!(
+            "duplicate packages ({} groups) -> {}",
+            groups.len(),
+            out_path.display()
+        );
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+async fn run(args: &Args, client: Arc<RpcClient>) -> Result<()> {
+    let dataset = args.dataset_options();
+    let inventory_opts = args.inventory_options();
+
+    // Handle two-version interface diff mode
+    if let Some(ref ids) = args.diff_packages {
+        let diff = match (&args.baseline_inventory, ids.as_slice()) {
+            (Some(path), [new_id]) => {
+                let old = load_package_inventory(path)?;
+                let new = load_own_package_inventory(
+                    Arc::clone(&client),
+                    &dataset,
+                    &inventory_opts,
+                    new_id,
+                )
+                .await?;
+                package_diff(&path.display().to_string(), &old, new_id, &new)
+            }
+            (None, [old_id, new_id]) => {
+                let (old, new) = tokio::try_join!(
+                    load_own_package_inventory(
+                        Arc::clone(&client),
+                        &dataset,
+                        &inventory_opts,
+                        old_id
+                    ),
+                    load_own_package_inventory(
+                        Arc::clone(&client),
+                        &dataset,
+                        &inventory_opts,
+                        new_id
+                    ),
+                )?;
+                package_diff(old_id, &old, new_id, &new)
+            }
+            (Some(_), _) => {
+                return Err(anyhow!(
+                    "--diff-packages takes only NEW_ID with --baseline-inventory"
+                ))
+            }
+            (None, _) => return Err(anyhow!("--diff-packages requires OLD_ID and NEW_ID")),
+        };
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+
+    // Handle three-way mode (current snapshot vs old snapshot vs live RPC)
+    if let Some(ref dirs) = args.three_way {
+        for package_id in collect_package_ids(args).await? {
+            let report = three_way_compare(
+                Arc::clone(&client),
+                &dataset,
+                &inventory_opts,
+                &dirs[0],
+                &dirs[1],
+                &package_id,
+            )
+            .await;
+            println!("{}", serde_json::to_string(&report)?);
+        }
+        return Ok(());
+    }
+
+    // Handle baseline drift check mode (live RPC vs committed inventories)
+    if let Some(ref dir) = args.baseline_dir {
+        let reports =
+            check_baseline_dir(Arc::clone(&client), &inventory_opts, dir, args.sort_fields).await?;
+        for report in &reports {
+            println!("{}", serde_json::to_string(report)?);
+        }
+        let drifted = reports.iter().filter(|r| !r.ok).count();
+        if drifted > 0 {
+            return Err(anyhow!(
+                "interface drift in {} of {} baseline packages",
+                drifted,
+                reports.len()
+            ));
+        }
+        return Ok(());
+    }
 
     // Handle corpus output mode (detailed stats matching extractor1)
     if let (Some(ref summary_path), Some(ref out_dir)) = (
         &args.verify_inventory_from_summary_jsonl,
         &args.corpus_out_dir,
     ) {
-        run_corpus_verification(&args, Arc::clone(&client), summary_path, out_dir).await?;
+        run_corpus_verification(args, Arc::clone(&client), summary_path, out_dir).await?;
         return Ok(());
     }
 
     // Handle verify-inventory mode (legacy simple output)
     if let Some(ref summary_path) = args.verify_inventory_from_summary_jsonl {
-        let out_path = run_verify_inventory(&args, Arc::clone(&client), summary_path).await?;
+        let out_path = run_verify_inventory(args, Arc::clone(&client), summary_path).await?;
         println!("inventory verified -> {}", out_path.display());
         return Ok(());
     }
 
-    // Handle index-from-summary-jsonl mode
-    if let Some(ref summary_path) = args.index_from_summary_jsonl {
-        let index = build_index_from_summary_jsonl(summary_path)?;
-        write_index_artifacts(&index, &args.index_out_dir)?;
-        println!("index artifacts -> {}", args.index_out_dir.display());
-        return Ok(());
-    }
-
     // Handle batch local bytecode mode
     if args.batch_local_bytecode_mainnet_most_used {
-        let package_ids =
-            iter_mainnet_most_used_package_ids(args.max_packages.unwrap_or(usize::MAX))?;
         let summary_path = args
             .summary_jsonl
             .clone()
             .unwrap_or_else(|| PathBuf::from("/tmp/bytecode_research_mainnet_most_used.jsonl"));
-        let out_file = std::fs::File::create(&summary_path)?;
-        let mut out = std::io::BufWriter::new(out_file);
-
-        for package_id in package_ids {
-            let row =
-                match run_single_local_sui_packages_with_rpc_deps(Arc::clone(&client), &package_id)
-                    .await
+        let summary_format = args.summary_format;
+        if args.resume && summary_format != SummaryFormat::Jsonl {
+            return Err(anyhow!("--resume requires --summary-format jsonl"));
+        }
+        let partial_path = partial_output_path(&summary_path);
+        let (processed, intact_len) = if args.resume {
+            // An interrupted run left its rows in the partial file; a finished one in the
+            // summary itself.
+            if !partial_path.exists() && summary_path.exists() {
+                fs::copy(&summary_path, &partial_path).with_context(|| {
+                    format!(
+                        "copy {} -> {}",
+                        summary_path.display(),
+                        partial_path.display()
+                    )
+                })?;
+            }
+            read_processed_summary_ids(&partial_path)?
+        } else {
+            (BTreeSet::new(), 0)
+        };
+        let mut package_ids =
+            iter_dataset_package_ids(&dataset, args.max_packages.unwrap_or(usize::MAX))?;
+        if let Some(shard) = args.shard {
+            package_ids = shard.select(package_ids);
+            tracing::info!(
+                shard = shard.index,
+                shards = shard.count,
+                packages = package_ids.len(),
+                "sharded batch"
+            );
+        }
+        package_ids.retain(|id| !processed.contains(id));
+        let total = package_ids.len();
+        if args.resume {
+            tracing::info!(
+                already_processed = processed.len(),
+                remaining = total,
+                "resuming batch"
+            );
+        }
+        let out_file = if args.resume {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&partial_path)
+                .with_context(|| format!("open {}", partial_path.display()))?;
+            file.set_len(intact_len)?;
+            file
+        } else {
+            create_partial_output(&summary_path)?
+        };
+        let mut out_writer = std::io::BufWriter::new(out_file);
+        if summary_format == SummaryFormat::Csv {
+            let header: Vec<String> = BATCH_SUMMARY_CSV_FIELDS
+                .iter()
+                .map(|f| f.to_string())
+                .collect();
+            write_csv_line(&mut out_writer, &header)?;
+        }
+        let out = Arc::new(std::sync::Mutex::new(out_writer));
+        let stats = Arc::new(std::sync::Mutex::new(BatchStats::default()));
+
+        // Rows are written as packages complete, so output order is arbitrary. Errored rows
+        // are held back when a retry pass will replace them.
+        let retry = args.retry_failed_packages;
+        let checkpoint = Arc::new(Checkpoint::new(
+            args.checkpoint_file.clone(),
+            package_ids.len(),
+        ));
+        let run_opts = Arc::new(LocalRunOptions::from_args(args));
+        prepare_dataset_index(&run_opts.dataset).await?;
+        let mut results = futures::stream::iter(package_ids.into_iter().map(|package_id| {
+            let client = Arc::clone(&client);
+            let run_opts = Arc::clone(&run_opts);
+            let out = Arc::clone(&out);
+            let stats = Arc::clone(&stats);
+            let dump_dir = args.dump_failing_bytecode.clone();
+            let checkpoint = Arc::clone(&checkpoint);
+            let skip_limit = args.skip_if_module_bytes_over;
+            let embed_sigs = args.embed_function_sigs;
+            tokio::spawn(async move {
+                checkpoint.begin(&package_id);
+                let row = match module_bytes_skip_reason(&run_opts.dataset, &package_id, skip_limit)
                 {
-                    Ok(v) => serde_json::json!({
-                        "resolved_package_id": package_id,
-                        "ok": v.stackless_error.is_none(),
-                        "module_names": v.module_names,
-                        "stackless_error": v.stackless_error,
-                    }),
-                    Err(e) => serde_json::json!({
+                    Some(reason) => json!({
                         "resolved_package_id": package_id,
                         "ok": false,
-                        "error": format!("{e:#}"),
+                        "skipped": reason,
                     }),
+                    None => {
+                        batch_summary_row(client, &run_opts, package_id.clone(), embed_sigs).await
+                    }
                 };
-            serde_json::to_writer(&mut out, &row)?;
-            out.write_all(b"\n")?;
+                checkpoint.record(row.get("ok") == Some(&Value::Bool(true)));
+                if retry && row.get("error").is_some() {
+                    return Ok(Some(row));
+                }
+                if row.get("ok") != Some(&Value::Bool(true)) {
+                    maybe_dump_failing_bytecode(
+                        &run_opts.dataset,
+                        dump_dir.as_deref(),
+                        &package_id,
+                    );
+                }
+                stats.lock().expect("batch stats lock").record(&row);
+                write_summary_row(&out, &row, summary_format)?;
+                Ok::<_, anyhow::Error>(None)
+            })
+        }))
+        .buffer_unordered(args.jobs.max(1));
+
+        let mut errored_rows = Vec::new();
+        let mut done = 0usize;
+        while let Some(joined) = results.next().await {
+            if let Some(row) = joined.context("batch task failed")?? {
+                errored_rows.push(row);
+            }
+            done += 1;
+            tracing::info!("{}/{} packages", done, total);
+        }
+        checkpoint.finish();
+
+        if retry {
+            retry_errored_rows(
+                &mut errored_rows,
+                args.retry_passes,
+                |r: &Value| {
+                    r.get("error")?;
+                    r.get("resolved_package_id")
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                },
+                |id| {
+                    batch_summary_row(Arc::clone(&client), &run_opts, id, args.embed_function_sigs)
+                },
+            )
+            .await;
+            for row in &errored_rows {
+                if row.get("ok") != Some(&Value::Bool(true)) {
+                    if let Some(id) = row.get("resolved_package_id").and_then(Value::as_str) {
+                        maybe_dump_failing_bytecode(
+                            &dataset,
+                            args.dump_failing_bytecode.as_deref(),
+                            id,
+                        );
+                    }
+                }
+                stats.lock().expect("batch stats lock").record(row);
+                write_summary_row(&out, row, summary_format)?;
+            }
         }
-        out.flush()?;
+
+        out.lock().expect("summary writer lock").flush()?;
+        commit_partial_output(&summary_path)?;
         println!("batch summary -> {}", summary_path.display());
+        if let Some(ref stats_path) = args.batch_stats_out {
+            let stats = stats.lock().expect("batch stats lock");
+            write_file_atomic(stats_path, &serde_json::to_vec_pretty(&stats.to_json())?)?;
+            println!(
+                "batch stats: {} packages, {} ok, {} errors, {} modules -> {}",
+                stats.total_packages,
+                stats.ok,
+                stats.total_packages - stats.ok,
+                stats.total_modules,
+                stats_path.display()
+            );
+        }
         return Ok(());
     }
 
     // Handle single package mode
-    let package_ids = collect_package_ids(&args)?;
+    let package_ids = collect_package_ids(args).await?;
     if package_ids.is_empty() {
-        eprintln!(
-            "No package IDs provided. Use --package-id, --package-ids-file, or --mvr-catalog."
+        tracing::error!(
+            "No package IDs provided. Use --package-id, --package-ids-file, --package-ids-stdin, --mvr-catalog, or --mvr-name."
         );
         std::process::exit(1);
     }
 
+    if let Some(ref out_dir) = args.export_generic_bounds {
+        fs::create_dir_all(out_dir)
+            .with_context(|| format!("failed to create out dir: {}", out_dir.display()))?;
+        for package_id in &package_ids {
+            let modules =
+                match load_root_package_modules(Arc::clone(&client), &dataset, package_id).await {
+                    Ok(v) => v,
+                    Err(e) => {
+                        tracing::error!(%package_id, error = %format!("{e:#}"), "load failed");
+                        continue;
+                    }
+                };
+            let bounds = generic_bounds_from_compiled_modules(package_id, &modules);
+            let out_path = out_dir.join(format!("{}.json", package_id));
+            write_file_atomic(&out_path, &serde_json::to_vec_pretty(&bounds)?)?;
+        }
+        println!("generic bounds -> {}", out_dir.display());
+        return Ok(());
+    }
+
+    if let Some(ref baseline_path) = args.interface_regression {
+        if package_ids.len() != 1 {
+            return Err(anyhow!(
+                "--interface-regression expects exactly one package id, got {}",
+                package_ids.len()
+            ));
+        }
+        let baseline = load_package_inventory(baseline_path)?;
+        let modules =
+            load_root_package_modules(Arc::clone(&client), &dataset, &package_ids[0]).await?;
+        let current = package_inventory_from_compiled_modules(&modules, &inventory_opts);
+        let report = interface_regressions(
+            &package_ids[0],
+            &baseline_path.display().to_string(),
+            &baseline,
+            &current,
+        );
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if !report.ok {
+            return Err(anyhow!(
+                "interface regression: {} removed, {} changed",
+                report.removed.len(),
+                report.changed.len()
+            ));
+        }
+        return Ok(());
+    }
+
+    if let Some(ref out_path) = args.emit_inventory_json {
+        if package_ids.len() != 1 {
+            return Err(anyhow!(
+                "--emit-inventory-json expects exactly one package id, got {}",
+                package_ids.len()
+            ));
+        }
+        let modules =
+            load_root_package_modules(Arc::clone(&client), &dataset, &package_ids[0]).await?;
+        let inventory = package_inventory_from_compiled_modules(&modules, &inventory_opts);
+        let mut value = serde_json::to_value(&inventory)?;
+        canonicalize_json_value(&mut value);
+        write_file_atomic(out_path, &serde_json::to_vec_pretty(&value)?)?;
+        println!("inventory -> {}", out_path.display());
+    }
+
+    if let Some(ref out_path) = args.emit_signatures {
+        if package_ids.len() != 1 {
+            return Err(anyhow!(
+                "--emit-signatures expects exactly one package id, got {}",
+                package_ids.len()
+            ));
+        }
+        let modules =
+            load_root_package_modules(Arc::clone(&client), &dataset, &package_ids[0]).await?;
+        let inventory = package_inventory_from_compiled_modules(&modules, &inventory_opts);
+        let mut text = String::new();
+        for line in public_function_signatures(&inventory) {
+            text.push_str(&line);
+            text.push('\n');
+        }
+        write_file_atomic(out_path, text.as_bytes())?;
+        println!("signatures -> {}", out_path.display());
+    }
+
+    if let Some(ref out_path) = args.emit_fingerprints {
+        let mut fingerprints: BTreeMap<&str, BTreeMap<String, String>> = BTreeMap::new();
+        for package_id in &package_ids {
+            let modules =
+                match load_root_package_modules(Arc::clone(&client), &dataset, package_id).await {
+                    Ok(v) => v,
+                    Err(e) => {
+                        tracing::error!(%package_id, error = %format!("{e:#}"), "load failed");
+                        continue;
+                    }
+                };
+            let inventory = package_inventory_from_compiled_modules(&modules, &inventory_opts);
+            fingerprints.insert(package_id, inventory.fingerprints());
+        }
+        write_file_atomic(out_path, &serde_json::to_vec_pretty(&fingerprints)?)?;
+        println!("fingerprints -> {}", out_path.display());
+    }
+
+    if let Some(ref out_dir) = args.emit_move_stub {
+        if package_ids.len() != 1 {
+            return Err(anyhow!(
+                "--emit-move-stub expects exactly one package id, got {}",
+                package_ids.len()
+            ));
+        }
+        let modules =
+            load_root_package_modules(Arc::clone(&client), &dataset, &package_ids[0]).await?;
+        let inventory = package_inventory_from_compiled_modules(&modules, &inventory_opts);
+        fs::create_dir_all(out_dir)
+            .with_context(|| format!("failed to create out dir: {}", out_dir.display()))?;
+        for (module_name, src) in render_move_stub(&inventory) {
+            let out_path = out_dir.join(format!("{}.move", module_name));
+            write_file_atomic(&out_path, src.as_bytes())?;
+        }
+        println!("move stubs -> {}", out_dir.display());
+    }
+
+    if let Some(ref out_dir) = args.emit_ts {
+        if package_ids.len() != 1 {
+            return Err(anyhow!(
+                "--emit-ts expects exactly one package id, got {}",
+                package_ids.len()
+            ));
+        }
+        let modules =
+            load_root_package_modules(Arc::clone(&client), &dataset, &package_ids[0]).await?;
+        let inventory = package_inventory_from_compiled_modules(&modules, &inventory_opts);
+        fs::create_dir_all(out_dir)
+            .with_context(|| format!("failed to create out dir: {}", out_dir.display()))?;
+        for (module_name, src) in render_ts_definitions(&package_ids[0], &inventory) {
+            let out_path = out_dir.join(format!("{}.ts", module_name));
+            write_file_atomic(&out_path, src.as_bytes())?;
+        }
+        println!("typescript definitions -> {}", out_dir.display());
+    }
+
+    if args.emit_dep_graph.is_some() && package_ids.len() != 1 {
+        return Err(anyhow!(
+            "--emit-dep-graph expects exactly one package id, got {}",
+            package_ids.len()
+        ));
+    }
+
+    if args.print_upgrade_chain {
+        for package_id in &package_ids {
+            let chain = resolve_upgrade_chain(Arc::clone(&client), package_id)
+                .await
+                .with_context(|| format!("resolve upgrade chain of {}", package_id))?;
+            println!(
+                "{}",
+                json!({ "package_id": package_id, "upgrade_chain": chain })
+            );
+        }
+    }
+
+    if args.single_out.is_some() && package_ids.len() != 1 {
+        return Err(anyhow!(
+            "--single-out expects exactly one package id, got {}",
+            package_ids.len()
+        ));
+    }
+
     // For now, just run single package extraction
+    let run_opts = LocalRunOptions::from_args(args);
     for package_id in &package_ids {
-        match run_single_local_sui_packages_with_rpc_deps(Arc::clone(&client), package_id).await {
+        match run_single_local_sui_packages_with_rpc_deps(
+            Arc::clone(&client),
+            &run_opts,
+            package_id,
+            false,
+        )
+        .await
+        {
             Ok(v) => {
+                if let Some(ref out_path) = args.single_out {
+                    let text = serde_json::to_string_pretty(&v)?;
+                    write_file_atomic(out_path, (text + "\n").as_bytes())?;
+                    println!("single package -> {}", out_path.display());
+                }
+                if let Some(ref out_path) = args.emit_dep_graph {
+                    write_file_atomic(out_path, v.dep_graph.to_dot().as_bytes())?;
+                    tracing::info!(
+                        nodes = v.dep_graph.nodes.len(),
+                        max_depth = v.dep_graph.max_depth(package_id),
+                        "dep graph"
+                    );
+                    println!("dep graph -> {}", out_path.display());
+                }
                 if args.list_modules {
-                    println!("Modules for {}: {:?}", package_id, v.module_names);
+                    eprintln!("Modules for {}: {:?}", package_id, v.module_names);
                 }
                 if let Some(ref err) = v.stackless_error {
-                    eprintln!("Stackless error for {}: {}", package_id, err);
+                    tracing::error!(%package_id, error = %err, "stackless error");
+                    maybe_dump_failing_bytecode(
+                        &dataset,
+                        args.dump_failing_bytecode.as_deref(),
+                        package_id,
+                    );
                 }
             }
             Err(e) => {
-                eprintln!("Error for {}: {:#}", package_id, e);
+                tracing::error!(%package_id, error = %format!("{e:#}"), "extraction failed");
+                maybe_dump_failing_bytecode(
+                    &dataset,
+                    args.dump_failing_bytecode.as_deref(),
+                    package_id,
+                );
             }
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_sample_is_pinned_per_seed() {
+        let ids: Vec<String> = (0..10).map(|i| format!("0x{i}")).collect();
+        let sample = select_verify_sample(ids.clone(), Some(42), Some(4));
+        assert_eq!(sample, ["0x0", "0x3", "0x5", "0x7"]);
+        assert_eq!(select_verify_sample(ids.clone(), None, Some(3)), ids[..3]);
+    }
+
+    #[test]
+    fn stackless_summary_counts_structs_of_translated_modules() {
+        use move_binary_format::file_format::{
+            empty_module, DatatypeHandleIndex, StructDefinition, StructFieldInformation,
+        };
+        use move_stackless_bytecode_2::ast::{Module, Package, StacklessBytecode};
+        use move_stackless_bytecode_2::translate::TranslationStats;
+
+        let with_structs = |n: usize| {
+            let mut m = empty_module();
+            m.struct_defs = (0..n)
+                .map(|_| StructDefinition {
+                    struct_handle: DatatypeHandleIndex(0),
+                    field_information: StructFieldInformation::Native,
+                })
+                .collect();
+            Arc::new(m)
+        };
+        let structs = struct_count(&[with_structs(2), with_structs(0), with_structs(3)]);
+        assert_eq!(structs, 5);
+
+        let bytecode = StacklessBytecode {
+            packages: vec![Package {
+                name: None,
+                address: AccountAddress::ZERO,
+                modules: BTreeMap::from([(
+                    "m".into(),
+                    Module {
+                        name: "m".into(),
+                        functions: BTreeMap::new(),
+                    },
+                )]),
+            }],
+        };
+        let summary = count_stackless(&bytecode, structs);
+        assert_eq!(
+            (summary.packages, summary.modules, summary.structs),
+            (1, 1, 5)
+        );
+
+        let stats = TranslationStats {
+            not_implemented_instructions: 4,
+            stack_underflow_pops: 1,
+        };
+        let summary = count_stackless_with_stats(&bytecode, stats, structs);
+        assert_eq!(summary.structs, 5);
+        assert_eq!(summary.not_implemented_instructions, 4);
+        assert_eq!(summary.stack_underflow_pops, 1);
+    }
+}
@@ -0,0 +1,1221 @@
+//! Sui JSON-RPC access: retrying/timed calls, the raw package cache, and package loading
+//! with RPC fallback for dependencies.
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use futures::future::BoxFuture;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use move_binary_format::CompiledModule;
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::dataset::{
+    normalize_package_id, parse_linkage_deps_from_artifact_dir,
+    sui_packages_artifact_dir_for_package_id, try_load_local_bytecode_modules,
+    try_load_local_modules_for_package, BytecodeModules, DatasetOptions, MvrNetwork,
+};
+use crate::graphql::GraphQlClient;
+use crate::inventory::{
+    canonicalize_json_value, is_framework_package, package_inventory_from_normalized_modules,
+    InventoryOptions, PackageInventory,
+};
+
+/// Per-RPC-method `(calls, total time)`, accumulated across the whole run.
+static RPC_METHOD_TIMINGS: std::sync::Mutex<BTreeMap<&'static str, (u64, Duration)>> =
+    std::sync::Mutex::new(BTreeMap::new());
+
+/// RPC attempts issued (retries included), and `RPC_CACHE_DIR` lookups that were served
+/// from disk or fell through to RPC.
+static RPC_CALLS: AtomicU64 = AtomicU64::new(0);
+static RPC_CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+static RPC_CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// Await an RPC future, charging its wall time to `method` in `RPC_METHOD_TIMINGS`.
+async fn timed_rpc<T>(method: &'static str, fut: impl std::future::Future<Output = T>) -> T {
+    RPC_CALLS.fetch_add(1, Ordering::Relaxed);
+    let start = Instant::now();
+    let out = fut.await;
+    let elapsed = start.elapsed();
+    tracing::debug!(method, elapsed_ms = elapsed.as_millis() as u64, "rpc call");
+    let mut timings = RPC_METHOD_TIMINGS.lock().expect("rpc timings lock");
+    let entry = timings.entry(method).or_default();
+    entry.0 += 1;
+    entry.1 += elapsed;
+    out
+}
+
+/// Retry settings applied by `rpc_call`, set once from the CLI in `main`.
+#[derive(Debug, Clone, Copy)]
+pub struct RpcRetryPolicy {
+    pub max_attempts: usize,
+    pub base_delay_ms: u64,
+}
+
+pub static RPC_RETRY_POLICY: std::sync::OnceLock<RpcRetryPolicy> = std::sync::OnceLock::new();
+
+fn rpc_retry_policy() -> RpcRetryPolicy {
+    RPC_RETRY_POLICY.get().copied().unwrap_or(RpcRetryPolicy {
+        max_attempts: 1,
+        base_delay_ms: 0,
+    })
+}
+
+/// Time limits applied to RPC work, set once from the CLI in `main`.
+#[derive(Debug, Clone, Copy)]
+pub struct RpcTimeouts {
+    /// Limit for a single RPC attempt; an elapsed attempt is retried like any other
+    /// transient failure.
+    pub call: Duration,
+    /// Limit for a whole `load_compiled_modules_with_rpc_deps` traversal, retries included.
+    pub package: Option<Duration>,
+}
+
+pub static RPC_TIMEOUTS: std::sync::OnceLock<RpcTimeouts> = std::sync::OnceLock::new();
+
+fn rpc_timeouts() -> RpcTimeouts {
+    RPC_TIMEOUTS.get().copied().unwrap_or(RpcTimeouts {
+        call: Duration::from_secs(30),
+        package: None,
+    })
+}
+
+/// Cap on simultaneous RPC requests across all tasks, set once from `--rpc-concurrency` in
+/// `main`; unset means unlimited.
+pub static RPC_CONCURRENCY: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+
+fn rpc_permits() -> Option<&'static tokio::sync::Semaphore> {
+    static PERMITS: std::sync::OnceLock<Option<tokio::sync::Semaphore>> =
+        std::sync::OnceLock::new();
+    PERMITS
+        .get_or_init(|| {
+            RPC_CONCURRENCY
+                .get()
+                .map(|&n| tokio::sync::Semaphore::new(n.max(1)))
+        })
+        .as_ref()
+}
+
+/// An RPC call or a package's dependency traversal ran past its time limit.
+#[derive(Debug)]
+pub struct RpcTimeout {
+    pub what: String,
+    pub limit: Duration,
+}
+
+impl std::fmt::Display for RpcTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rpc_timeout: {} exceeded {}s",
+            self.what,
+            self.limit.as_secs_f64()
+        )
+    }
+}
+
+impl std::error::Error for RpcTimeout {}
+
+/// Whether `e` (or anything it wraps) is an `RpcTimeout`.
+pub fn is_rpc_timeout(e: &anyhow::Error) -> bool {
+    e.chain().any(|c| c.is::<RpcTimeout>())
+}
+
+/// Errors worth retrying: timeouts, connection failures, rate limiting and 5xx responses.
+/// Anything else (bad params, missing objects) fails immediately. Typed errors anywhere in
+/// the chain decide; only errors we can't downcast (the SDK's JSON-RPC client) fall back to
+/// `is_transient_rpc_message`.
+fn is_transient_rpc_error(e: &anyhow::Error) -> bool {
+    for cause in e.chain() {
+        if cause.is::<RpcTimeout>() {
+            return true;
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return e.is_timeout()
+                || e.is_connect()
+                || e.status().is_some_and(is_transient_http_status);
+        }
+        if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind::*;
+            return matches!(
+                e.kind(),
+                TimedOut | ConnectionReset | ConnectionAborted | ConnectionRefused | BrokenPipe
+            );
+        }
+    }
+    is_transient_rpc_message(&e.to_string())
+}
+
+fn is_transient_http_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Fallback for untyped errors: known transport phrases, or a whole `429` / `5xx` token
+/// (so ids, byte counts and the like containing "500" don't count).
+fn is_transient_rpc_message(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    let phrase = [
+        "timed out",
+        "request timeout",
+        "networking or low-level protocol error",
+        "connection reset",
+        "connection refused",
+        "connection closed",
+        "reset by peer",
+        "broken pipe",
+        "restart needed",
+        "restart required",
+        "too many requests",
+        "temporarily unavailable",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle));
+    phrase
+        || message
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|token| token.len() == 3)
+            .filter_map(|token| token.parse::<u16>().ok())
+            .filter_map(|code| reqwest::StatusCode::from_u16(code).ok())
+            .any(is_transient_http_status)
+}
+
+/// Exponential backoff (`base * 2^(attempt-1)`, capped at 30s) with up to 50% jitter.
+fn backoff_delay(base_delay_ms: u64, attempt: usize) -> Duration {
+    let exp = base_delay_ms.saturating_mul(1u64 << (attempt - 1).min(16));
+    let capped = exp.min(30_000);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let jitter = nanos % (capped / 2 + 1);
+    Duration::from_millis(capped + jitter)
+}
+
+/// Run `f` up to `max_attempts` times, sleeping with jittered exponential backoff between
+/// attempts. Only transient errors (see `is_transient_rpc_error`) are retried.
+async fn with_retry<T, Fut>(
+    max_attempts: usize,
+    base_delay_ms: u64,
+    mut f: impl FnMut() -> Fut,
+) -> Result<T>
+where
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Ok(v) => return Ok(v),
+            Err(e) if attempt < max_attempts && is_transient_rpc_error(&e) => {
+                let delay = backoff_delay(base_delay_ms, attempt);
+                tracing::warn!(
+                    attempt,
+                    max_attempts,
+                    error = %e,
+                    ?delay,
+                    "rpc attempt failed; retrying"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Issue one RPC call under the configured retry policy and per-call timeout, timing
+/// every attempt. Each attempt holds an `RPC_CONCURRENCY` permit; waiting for one does not
+/// count against the timeout.
+pub async fn rpc_call<T, E, Fut>(method: &'static str, mut f: impl FnMut() -> Fut) -> Result<T>
+where
+    Fut: std::future::Future<Output = std::result::Result<T, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let policy = rpc_retry_policy();
+    let limit = rpc_timeouts().call;
+    with_retry(policy.max_attempts, policy.base_delay_ms, || {
+        let attempt = timed_rpc(method, f());
+        async move {
+            let _permit = match rpc_permits() {
+                Some(permits) => Some(permits.acquire().await.expect("rpc semaphore open")),
+                None => None,
+            };
+            match tokio::time::timeout(limit, attempt).await {
+                Ok(res) => res.map_err(anyhow::Error::from),
+                Err(_) => Err(anyhow::Error::new(RpcTimeout {
+                    what: method.to_string(),
+                    limit,
+                })),
+            }
+        }
+    })
+    .await
+}
+
+#[derive(Debug, Serialize)]
+pub struct RpcMethodMetrics {
+    pub calls: u64,
+    pub total_ms: u64,
+}
+
+/// RPC counters for the run so far, for `--metrics-out`.
+#[derive(Debug, Serialize)]
+pub struct RpcMetrics {
+    pub rpc_calls: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub methods: BTreeMap<&'static str, RpcMethodMetrics>,
+}
+
+pub fn rpc_metrics() -> RpcMetrics {
+    let timings = RPC_METHOD_TIMINGS.lock().expect("rpc timings lock");
+    RpcMetrics {
+        rpc_calls: RPC_CALLS.load(Ordering::Relaxed),
+        cache_hits: RPC_CACHE_HITS.load(Ordering::Relaxed),
+        cache_misses: RPC_CACHE_MISSES.load(Ordering::Relaxed),
+        methods: timings
+            .iter()
+            .map(|(method, (calls, total))| {
+                (
+                    *method,
+                    RpcMethodMetrics {
+                        calls: *calls,
+                        total_ms: total.as_millis() as u64,
+                    },
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Which fullnode API serves package reads.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum RpcKind {
+    Jsonrpc,
+    Graphql,
+}
+
+/// The package reads the extractor needs, independent of the API that serves them.
+/// Implementations apply the `rpc_call` retry and timeout policy themselves.
+pub trait PackageRpc: Send + Sync {
+    /// The package object's current digest, or `None` if the object does not exist.
+    fn package_digest(&self, package_id: ObjectID) -> BoxFuture<'_, Result<Option<String>>>;
+
+    /// The package's object digest, module bytes and linkage table. `method` labels the
+    /// call in `--print-rpc-method-timings`.
+    fn package_contents(
+        &self,
+        package_id: ObjectID,
+        method: &'static str,
+    ) -> BoxFuture<'_, Result<(String, RawPackage)>>;
+
+    /// The package's modules in the `getNormalizedMoveModulesByPackage` JSON shape, keyed
+    /// by module name.
+    fn normalized_modules(&self, package_id: ObjectID) -> BoxFuture<'_, Result<Value>>;
+}
+
+impl PackageRpc for sui_sdk::SuiClient {
+    fn package_digest(&self, package_id: ObjectID) -> BoxFuture<'_, Result<Option<String>>> {
+        Box::pin(async move {
+            let resp = rpc_call("get_object_with_options(digest)", || {
+                self.read_api().get_object_with_options(
+                    package_id,
+                    sui_sdk::rpc_types::SuiObjectDataOptions::new(),
+                )
+            })
+            .await?;
+            Ok(resp.data.map(|data| data.digest.to_string()))
+        })
+    }
+
+    fn package_contents(
+        &self,
+        package_id: ObjectID,
+        method: &'static str,
+    ) -> BoxFuture<'_, Result<(String, RawPackage)>> {
+        Box::pin(async move {
+            // Fetch raw package object and extract module bytes.
+            let resp = rpc_call(method, || {
+                self.read_api().get_object_with_options(
+                    package_id,
+                    sui_sdk::rpc_types::SuiObjectDataOptions::new().with_bcs(),
+                )
+            })
+            .await
+            .with_context(|| format!("fetch package object {}", package_id))?;
+
+            let data = resp
+                .data
+                .ok_or_else(|| anyhow!("missing object data for {}", package_id))?;
+            let digest = data.digest.to_string();
+            let version = data.version.value();
+            let bcs = data
+                .bcs
+                .ok_or_else(|| anyhow!("missing bcs for {}", package_id))?;
+
+            let pkg = match bcs {
+                sui_sdk::rpc_types::SuiRawData::Package(pkg) => pkg,
+                _ => return Err(anyhow!("object {} is not a package", package_id)),
+            };
+
+            Ok((
+                digest,
+                RawPackage {
+                    module_map: pkg.module_map.into_iter().collect(),
+                    linkage: pkg
+                        .linkage_table
+                        .into_keys()
+                        .map(|id| id.to_string())
+                        .collect(),
+                    version: Some(version),
+                },
+            ))
+        })
+    }
+
+    fn normalized_modules(&self, package_id: ObjectID) -> BoxFuture<'_, Result<Value>> {
+        Box::pin(async move {
+            let modules = rpc_call("get_normalized_move_modules_by_package", || {
+                self.read_api()
+                    .get_normalized_move_modules_by_package(package_id)
+            })
+            .await?;
+            serde_json::to_value(&modules).context("serialize normalized modules")
+        })
+    }
+}
+
+/// Fullnode connection selected by `--rpc-kind`. Package reads go through `PackageRpc`;
+/// source digest and upgrade policy lookups need JSON-RPC.
+pub struct RpcClient {
+    /// URL the client was connected to.
+    endpoint: String,
+    api: RpcApi,
+}
+
+enum RpcApi {
+    JsonRpc(sui_sdk::SuiClient),
+    GraphQl(GraphQlClient),
+}
+
+impl RpcClient {
+    pub async fn connect(kind: RpcKind, url: &str) -> Result<Self> {
+        let api = match kind {
+            RpcKind::Jsonrpc => RpcApi::JsonRpc(
+                sui_sdk::SuiClientBuilder::default()
+                    .build(url)
+                    .await
+                    .context("build sui client")?,
+            ),
+            RpcKind::Graphql => RpcApi::GraphQl(GraphQlClient::new(url)),
+        };
+        Ok(RpcClient {
+            endpoint: url.to_string(),
+            api,
+        })
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    pub fn packages(&self) -> &dyn PackageRpc {
+        match &self.api {
+            RpcApi::JsonRpc(client) => client,
+            RpcApi::GraphQl(client) => client,
+        }
+    }
+
+    fn jsonrpc(&self, what: &str) -> Result<&sui_sdk::SuiClient> {
+        match &self.api {
+            RpcApi::JsonRpc(client) => Ok(client),
+            RpcApi::GraphQl(_) => Err(anyhow!("{} requires --rpc-kind jsonrpc", what)),
+        }
+    }
+}
+
+/// Root of the on-disk package cache, set once from `--rpc-cache-dir` in `main`.
+pub static RPC_CACHE_DIR: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+
+/// A package's raw module bytes and the package ids from its linkage table.
+pub struct RawPackage {
+    pub module_map: BTreeMap<String, Vec<u8>>,
+    pub linkage: Vec<String>,
+    /// `None` for cache entries written before versions were recorded.
+    pub version: Option<u64>,
+}
+
+/// Cache entries live at `<cache>/<object_id>/<object_digest>/`, so an in-place upgrade
+/// (system packages) changes the digest and simply misses the stale entry.
+fn rpc_cache_entry_dir(cache_dir: &Path, package_id: ObjectID, digest: &str) -> PathBuf {
+    cache_dir.join(package_id.to_string()).join(digest)
+}
+
+fn read_cached_raw_package(entry_dir: &Path) -> Result<RawPackage> {
+    let mut module_map = BTreeMap::new();
+    let modules_dir = entry_dir.join("bytecode_modules");
+    for entry in
+        fs::read_dir(&modules_dir).with_context(|| format!("read_dir {}", modules_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("mv") {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| anyhow!("bad module file name {}", path.display()))?
+            .to_string();
+        let bytes = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+        module_map.insert(name, bytes);
+    }
+    let linkage_path = entry_dir.join("linkage.json");
+    let linkage: Vec<String> = serde_json::from_str(
+        &fs::read_to_string(&linkage_path)
+            .with_context(|| format!("read {}", linkage_path.display()))?,
+    )
+    .with_context(|| format!("parse {}", linkage_path.display()))?;
+    let version = fs::read_to_string(entry_dir.join("version"))
+        .ok()
+        .and_then(|s| s.trim().parse().ok());
+    Ok(RawPackage {
+        module_map,
+        linkage,
+        version,
+    })
+}
+
+/// Distinguishes temp directories of concurrent writers within one process (`--jobs`).
+static CACHE_TMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Write into a sibling temp directory and rename it into place, so a concurrent or
+/// interrupted writer never leaves a half-populated entry behind.
+fn write_cached_raw_package(entry_dir: &Path, pkg: &RawPackage) -> Result<()> {
+    let seq = CACHE_TMP_SEQ.fetch_add(1, Ordering::Relaxed);
+    let tmp_dir = entry_dir.with_extension(format!("tmp.{}.{seq}", std::process::id()));
+    let modules_dir = tmp_dir.join("bytecode_modules");
+    fs::create_dir_all(&modules_dir)
+        .with_context(|| format!("create {}", modules_dir.display()))?;
+    for (name, bytes) in &pkg.module_map {
+        let path = modules_dir.join(format!("{name}.mv"));
+        fs::write(&path, bytes).with_context(|| format!("write {}", path.display()))?;
+    }
+    let linkage_path = tmp_dir.join("linkage.json");
+    fs::write(&linkage_path, serde_json::to_string(&pkg.linkage)?)
+        .with_context(|| format!("write {}", linkage_path.display()))?;
+    if let Some(version) = pkg.version {
+        let version_path = tmp_dir.join("version");
+        fs::write(&version_path, version.to_string())
+            .with_context(|| format!("write {}", version_path.display()))?;
+    }
+    if fs::rename(&tmp_dir, entry_dir).is_err() {
+        // Another writer got there first; its entry is equivalent.
+        let _ = fs::remove_dir_all(&tmp_dir);
+    }
+    Ok(())
+}
+
+/// Fetch a package's raw modules and linkage table, going through `RPC_CACHE_DIR` when set.
+/// A cache hit still costs one lightweight object read to confirm the digest is current.
+async fn fetch_raw_package_via_rpc(
+    client: Arc<RpcClient>,
+    package_id: ObjectID,
+    method: &'static str,
+) -> Result<RawPackage> {
+    let cache_dir = RPC_CACHE_DIR.get();
+    if let Some(cache_dir) = cache_dir {
+        let digest = client
+            .packages()
+            .package_digest(package_id)
+            .await
+            .with_context(|| format!("fetch package digest {}", package_id))?;
+        if let Some(digest) = digest {
+            let entry_dir = rpc_cache_entry_dir(cache_dir, package_id, &digest);
+            if entry_dir.is_dir() {
+                match read_cached_raw_package(&entry_dir) {
+                    Ok(pkg) => {
+                        tracing::debug!(%package_id, "rpc cache hit");
+                        RPC_CACHE_HITS.fetch_add(1, Ordering::Relaxed);
+                        return Ok(pkg);
+                    }
+                    Err(e) => tracing::warn!(
+                        entry = %entry_dir.display(),
+                        error = %format!("{e:#}"),
+                        "ignoring unreadable cache entry"
+                    ),
+                }
+            } else {
+                tracing::debug!(%package_id, "rpc cache miss");
+            }
+        }
+        RPC_CACHE_MISSES.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let (digest, raw) = client
+        .packages()
+        .package_contents(package_id, method)
+        .await?;
+    if let Some(cache_dir) = cache_dir {
+        let entry_dir = rpc_cache_entry_dir(cache_dir, package_id, &digest);
+        if let Err(e) = write_cached_raw_package(&entry_dir, &raw) {
+            tracing::warn!(
+                entry = %entry_dir.display(),
+                error = %format!("{e:#}"),
+                "failed to write cache entry"
+            );
+        }
+    }
+    Ok(raw)
+}
+
+async fn fetch_compiled_modules_via_rpc(
+    client: Arc<RpcClient>,
+    package_id: ObjectID,
+) -> Result<BytecodeModules> {
+    let pkg =
+        fetch_raw_package_via_rpc(client, package_id, "get_object_with_options(modules)").await?;
+
+    let mut modules = Vec::new();
+    for (name, bytes) in pkg.module_map {
+        let module = CompiledModule::deserialize_with_defaults(&bytes)
+            .with_context(|| format!("deserialize rpc module {}::{}", package_id, name))?;
+        modules.push(module);
+    }
+    Ok(BytecodeModules {
+        modules,
+        skipped: 0,
+        version: pkg.version,
+    })
+}
+
+async fn fetch_dependency_package_ids_via_rpc(
+    client: Arc<RpcClient>,
+    package_id: ObjectID,
+) -> Result<Vec<String>> {
+    let pkg =
+        fetch_raw_package_via_rpc(client, package_id, "get_object_with_options(deps)").await?;
+    Ok(pkg.linkage)
+}
+
+/// Cap on linkage hops followed by `load_compiled_modules_with_rpc_deps`, set once from
+/// `--max-dep-depth`. Unset means unbounded; 0 loads the root package only.
+pub static MAX_DEP_DEPTH: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+
+/// When set (from `--fail-on-dep-cycle`), `load_compiled_modules_with_rpc_deps` fails with
+/// `DependencyCycle` instead of reporting linkage cycles alongside the modules.
+pub static FAIL_ON_DEP_CYCLE: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+
+/// A package's linkage graph contains at least one cycle.
+#[derive(Debug)]
+pub struct DependencyCycle {
+    pub package_id: String,
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl std::fmt::Display for DependencyCycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cycles: Vec<String> = self
+            .cycles
+            .iter()
+            .map(|cycle| format!("{} -> {}", cycle.join(" -> "), cycle[0]))
+            .collect();
+        write!(
+            f,
+            "dependency_cycle: {} links cyclically: {}",
+            self.package_id,
+            cycles.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for DependencyCycle {}
+
+/// Modules loaded for a package and its dependency closure.
+pub struct PackageModulesWithDeps {
+    /// Shared with the framework package cache; deep-copy only where a consumer needs
+    /// owned modules (stackless translation).
+    pub modules: Vec<Arc<CompiledModule>>,
+    /// Dependency ids that `MAX_DEP_DEPTH` kept from being loaded. Non-empty means the
+    /// module set is partial.
+    pub truncated_deps: BTreeSet<String>,
+    /// Linkage cycles found while loading, each starting at the package its back-edge
+    /// returns to (see `DepGraph::cycles`).
+    pub dependency_cycle: Vec<Vec<String>>,
+    /// Local `.mv` files skipped under `--skip-bad-modules`, across the root and its deps.
+    pub skipped_modules: usize,
+    /// The root package's on-chain version; `None` when its source doesn't record one.
+    pub version: Option<u64>,
+    /// Self address of the root package's modules (its original id); `None` if it has none.
+    pub root_address: Option<move_core_types::account_address::AccountAddress>,
+    /// Every package loaded and linkage edge seen. Edges to deps cut by `MAX_DEP_DEPTH`
+    /// are kept, their targets just have no node.
+    pub dep_graph: DepGraph,
+}
+
+/// Load a package's modules plus its transitive dependencies over RPC, bounded by
+/// `RpcTimeouts::package` when set so one slow package cannot stall a batch.
+pub async fn load_compiled_modules_with_rpc_deps(
+    client: Arc<RpcClient>,
+    dataset: &DatasetOptions,
+    root_package_id: &str,
+) -> Result<PackageModulesWithDeps> {
+    let traversal = load_compiled_modules_with_rpc_deps_unbounded(client, dataset, root_package_id);
+    match rpc_timeouts().package {
+        Some(limit) => tokio::time::timeout(limit, traversal).await.map_err(|_| {
+            anyhow::Error::new(RpcTimeout {
+                what: format!("package {}", root_package_id),
+                limit,
+            })
+        })?,
+        None => traversal.await,
+    }
+}
+
+async fn load_compiled_modules_with_rpc_deps_unbounded(
+    client: Arc<RpcClient>,
+    dataset: &DatasetOptions,
+    root_package_id: &str,
+) -> Result<PackageModulesWithDeps> {
+    let max_depth = MAX_DEP_DEPTH.get().copied();
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    let mut graph = DepGraph::default();
+    let mut truncated_deps: BTreeSet<String> = BTreeSet::new();
+    // Breadth-first, so each package is reached at its shallowest depth.
+    let mut queue: VecDeque<(String, usize)> = VecDeque::from([(root_package_id.to_string(), 0)]);
+    let mut all_modules: Vec<Arc<CompiledModule>> = Vec::new();
+    let mut skipped_modules = 0usize;
+    let mut version = None;
+    let mut root_address = None;
+
+    while let Some((pid, depth)) = queue.pop_front() {
+        if !seen.insert(pid.clone()) {
+            continue;
+        }
+
+        let loaded = load_package_modules_and_linkage(&client, dataset, &pid).await?;
+        if depth == 0 {
+            version = loaded.version;
+            root_address = loaded.modules.first().map(|m| *m.address());
+        }
+        all_modules.extend(loaded.modules.iter().cloned());
+        skipped_modules += loaded.skipped;
+        graph.nodes.insert(pid.clone(), loaded.source);
+
+        for dep in loaded.deps.iter().cloned() {
+            graph.edges.insert((pid.clone(), dep.clone()));
+            if seen.contains(&dep) {
+                continue;
+            }
+            if max_depth.is_some_and(|max| depth >= max) {
+                truncated_deps.insert(dep);
+            } else {
+                queue.push_back((dep, depth + 1));
+            }
+        }
+    }
+
+    // A dep cut off on one path may still have been loaded through a shorter one.
+    truncated_deps.retain(|dep| !seen.contains(dep));
+    if !truncated_deps.is_empty() {
+        tracing::info!(
+            package_id = %root_package_id,
+            max_dep_depth = max_depth,
+            truncated = truncated_deps.len(),
+            "dependency graph truncated"
+        );
+    }
+
+    let dependency_cycle = graph.cycles(root_package_id);
+    if !dependency_cycle.is_empty() {
+        if FAIL_ON_DEP_CYCLE.get().copied().unwrap_or(false) {
+            return Err(anyhow::Error::new(DependencyCycle {
+                package_id: root_package_id.to_string(),
+                cycles: dependency_cycle,
+            }));
+        }
+        tracing::warn!(
+            package_id = %root_package_id,
+            cycles = dependency_cycle.len(),
+            "dependency cycle in linkage graph"
+        );
+    }
+
+    Ok(PackageModulesWithDeps {
+        modules: all_modules,
+        truncated_deps,
+        dependency_cycle,
+        skipped_modules,
+        version,
+        root_address,
+        dep_graph: graph,
+    })
+}
+
+type LoadedPackageCell = Arc<tokio::sync::OnceCell<Arc<LoadedPackage>>>;
+
+/// One package's modules, the ids in its linkage table, and where it was loaded from.
+struct LoadedPackage {
+    modules: Vec<Arc<CompiledModule>>,
+    skipped: usize,
+    version: Option<u64>,
+    deps: Vec<String>,
+    source: &'static str,
+}
+
+/// `FRAMEWORK_PACKAGES` key: the dataset and RPC endpoint a package was loaded through,
+/// and its `normalize_package_id`.
+type FrameworkPackageKey = (DatasetOptions, String, String);
+
+/// Framework packages. Nearly every package depends on them, so across a batch each is
+/// loaded once and its modules shared by reference. Keys include where a package was
+/// loaded from, so clients of other networks never see each other's copies. The per-key
+/// cell makes concurrent first requests wait for one load instead of racing.
+static FRAMEWORK_PACKAGES: std::sync::OnceLock<
+    std::sync::Mutex<std::collections::HashMap<FrameworkPackageKey, LoadedPackageCell>>,
+> = std::sync::OnceLock::new();
+
+/// `load_package_modules_uncached`, served from `FRAMEWORK_PACKAGES` for framework packages.
+async fn load_package_modules_and_linkage(
+    client: &Arc<RpcClient>,
+    dataset: &DatasetOptions,
+    pid: &str,
+) -> Result<Arc<LoadedPackage>> {
+    if !is_framework_package(pid) {
+        return load_package_modules_uncached(client, dataset, pid)
+            .await
+            .map(Arc::new);
+    }
+    let cell = FRAMEWORK_PACKAGES
+        .get_or_init(Default::default)
+        .lock()
+        .expect("framework package cache lock")
+        .entry((
+            dataset.clone(),
+            client.endpoint().to_string(),
+            normalize_package_id(pid),
+        ))
+        .or_default()
+        .clone();
+    // A failed load leaves the cell empty, so the next request retries it.
+    let loaded = cell
+        .get_or_try_init(|| async {
+            load_package_modules_uncached(client, dataset, pid)
+                .await
+                .map(Arc::new)
+        })
+        .await?;
+    Ok(Arc::clone(loaded))
+}
+
+/// Load one package's modules (local dataset first, then RPC), the ids in its linkage table,
+/// and which of the two it came from.
+async fn load_package_modules_uncached(
+    client: &Arc<RpcClient>,
+    dataset: &DatasetOptions,
+    pid: &str,
+) -> Result<LoadedPackage> {
+    let loaded = |modules: BytecodeModules, deps, source| LoadedPackage {
+        modules: modules.modules.into_iter().map(Arc::new).collect(),
+        skipped: modules.skipped,
+        version: modules.version,
+        deps,
+        source,
+    };
+    if let Some(local_mods) = try_load_local_bytecode_modules(dataset, pid)? {
+        // Only local artifacts have bcs.json linkage info.
+        let artifact_dir = sui_packages_artifact_dir_for_package_id(dataset, pid)?
+            .canonicalize()
+            .with_context(|| format!("canonicalize artifact dir for {}", pid))?;
+        let deps = parse_linkage_deps_from_artifact_dir(&artifact_dir)?;
+        return Ok(loaded(local_mods, deps, "local"));
+    }
+
+    // Not in local dataset; fetch from RPC.
+    // Dependency IDs may be 0x-prefixed 64-hex; ObjectID::from_str expects the same.
+    let oid =
+        object_id_from_hex_str(pid).map_err(|e| anyhow!("invalid package id {}: {}", pid, e))?;
+
+    // Discover additional deps from the on-chain linkage table.
+    let deps = fetch_dependency_package_ids_via_rpc(Arc::clone(client), oid)
+        .await
+        .unwrap_or_default();
+
+    let rpc_mods = fetch_compiled_modules_via_rpc(Arc::clone(client), oid).await?;
+    Ok(loaded(rpc_mods, deps, "rpc"))
+}
+
+/// Package dependency edges discovered by walking linkage tables from a root package.
+#[derive(Debug, Default)]
+pub struct DepGraph {
+    /// Package id -> where its modules/linkage come from (`local` or `rpc`).
+    pub nodes: BTreeMap<String, &'static str>,
+    pub edges: BTreeSet<(String, String)>,
+}
+
+impl DepGraph {
+    fn children(&self) -> BTreeMap<&str, Vec<&str>> {
+        let mut children: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (from, to) in &self.edges {
+            children.entry(from).or_default().push(to);
+        }
+        children
+    }
+
+    /// Longest shortest-path distance from `root` over the recorded edges.
+    pub fn max_depth(&self, root: &str) -> usize {
+        let children = self.children();
+        let mut depth: BTreeMap<&str, usize> = BTreeMap::from([(root, 0)]);
+        let mut queue = VecDeque::from([root]);
+        while let Some(node) = queue.pop_front() {
+            let d = depth[node];
+            for &child in children.get(node).into_iter().flatten() {
+                if !depth.contains_key(child) {
+                    depth.insert(child, d + 1);
+                    queue.push_back(child);
+                }
+            }
+        }
+        depth.values().copied().max().unwrap_or(0)
+    }
+
+    /// Cycles reachable from `root`, one per back-edge of a depth-first walk (an edge into
+    /// a package still on the walk's stack). Each cycle lists the packages from the
+    /// back-edge's target to its source; the closing edge back to the first is implied.
+    pub fn cycles(&self, root: &str) -> Vec<Vec<String>> {
+        fn visit<'a>(
+            node: &'a str,
+            children: &BTreeMap<&'a str, Vec<&'a str>>,
+            stack: &mut Vec<&'a str>,
+            done: &mut BTreeSet<&'a str>,
+            cycles: &mut Vec<Vec<String>>,
+        ) {
+            stack.push(node);
+            for &child in children.get(node).into_iter().flatten() {
+                if let Some(pos) = stack.iter().position(|&n| n == child) {
+                    cycles.push(stack[pos..].iter().map(|n| n.to_string()).collect());
+                } else if !done.contains(child) {
+                    visit(child, children, stack, done, cycles);
+                }
+            }
+            stack.pop();
+            done.insert(node);
+        }
+
+        let children = self.children();
+        let mut cycles = Vec::new();
+        visit(
+            root,
+            &children,
+            &mut Vec::new(),
+            &mut BTreeSet::new(),
+            &mut cycles,
+        );
+        cycles
+    }
+
+    pub fn to_dot(&self) -> String {
+        fn short(id: &str) -> String {
+            let hex = id.strip_prefix("0x").unwrap_or(id).trim_start_matches('0');
+            if hex.len() <= 10 {
+                format!("0x{}", if hex.is_empty() { "0" } else { hex })
+            } else {
+                format!("0x{}..{}", &hex[..6], &hex[hex.len() - 4..])
+            }
+        }
+        let mut out = String::from("digraph deps {\n");
+        for (id, source) in &self.nodes {
+            out.push_str(&format!(
+                "  \"{}\" [label=\"{}\\n{}\"];\n",
+                id,
+                short(id),
+                source
+            ));
+        }
+        for (from, to) in &self.edges {
+            out.push_str(&format!("  \"{}\" -> \"{}\";\n", from, to));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Load only the root package's own modules: from the local dataset when present,
+/// otherwise from RPC. Dependencies are not resolved.
+pub async fn load_root_package_modules(
+    client: Arc<RpcClient>,
+    dataset: &DatasetOptions,
+    package_id: &str,
+) -> Result<Vec<CompiledModule>> {
+    if let Some(mods) = try_load_local_modules_for_package(dataset, package_id)? {
+        return Ok(mods);
+    }
+    let oid = object_id_from_hex_str(package_id)
+        .map_err(|e| anyhow!("invalid package id {}: {}", package_id, e))?;
+    Ok(fetch_compiled_modules_via_rpc(client, oid).await?.modules)
+}
+
+/// Resolve an MVR name like `@namespace/app` to its package id on `network`.
+pub async fn resolve_mvr_name(
+    client: &reqwest::Client,
+    name: &str,
+    network: MvrNetwork,
+) -> Result<String> {
+    let base = network
+        .mvr_api_url()
+        .ok_or_else(|| anyhow!("mvr name {}: no MVR resolver for {:?}", name, network))?;
+    let url = format!("{}/v1/resolution/{}", base, name);
+    let resp = client
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("mvr name {}: request {}", name, url))?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(anyhow!("mvr name {}: resolver returned {}", name, status));
+    }
+    let body: Value = resp
+        .json()
+        .await
+        .with_context(|| format!("mvr name {}: parse resolver response", name))?;
+    body.get("package_id")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("mvr name {}: resolver response has no package_id", name))
+}
+
+/// Fetch a package's normalized modules over RPC and parse them into an inventory.
+pub async fn fetch_rpc_package_inventory(
+    client: Arc<RpcClient>,
+    package_id: &str,
+    opts: &InventoryOptions,
+) -> Result<PackageInventory> {
+    let oid = object_id_from_hex_str(package_id)
+        .map_err(|e| anyhow!("invalid package id {}: {}", package_id, e))?;
+    let mut value = client
+        .packages()
+        .normalized_modules(oid)
+        .await
+        .with_context(|| format!("fetch normalized modules {}", package_id))?;
+    canonicalize_json_value(&mut value);
+    package_inventory_from_normalized_modules(&value, opts)
+}
+
+/// Fetch the source digest published with an on-chain package, if it carries one. Only
+/// the package content's own `sourceDigest` counts, not a same-named field elsewhere in
+/// the response.
+pub async fn fetch_source_digest_via_rpc(
+    client: Arc<RpcClient>,
+    package_id: ObjectID,
+) -> Result<Option<String>> {
+    let jsonrpc = client.jsonrpc("source digest lookup")?;
+    let resp = rpc_call("get_object_with_options(source_digest)", || {
+        jsonrpc.read_api().get_object_with_options(
+            package_id,
+            sui_sdk::rpc_types::SuiObjectDataOptions::new().with_content(),
+        )
+    })
+    .await
+    .with_context(|| format!("fetch package object {}", package_id))?;
+    let value = serde_json::to_value(&resp)?;
+    Ok(value
+        .pointer("/data/content/sourceDigest")
+        .and_then(Value::as_str)
+        .map(|s| s.to_string()))
+}
+
+/// Name of an `UpgradeCap` policy byte (see `sui::package`).
+fn upgrade_policy_name(policy: u64) -> String {
+    match policy {
+        0 => "compatible".to_string(),
+        128 => "additive".to_string(),
+        192 => "dep_only".to_string(),
+        other => format!("unknown({other})"),
+    }
+}
+
+/// Find the package's `UpgradeCap` among the object changes of the transaction that last
+/// published or upgraded it. Returns `None` when no cap is involved, e.g. for genesis
+/// system packages.
+async fn find_upgrade_cap_via_rpc(
+    jsonrpc: &sui_sdk::SuiClient,
+    package_id: ObjectID,
+) -> Result<Option<ObjectID>> {
+    let resp = rpc_call("get_object_with_options(previous_transaction)", || {
+        jsonrpc.read_api().get_object_with_options(
+            package_id,
+            sui_sdk::rpc_types::SuiObjectDataOptions::new().with_previous_transaction(),
+        )
+    })
+    .await
+    .with_context(|| format!("fetch package object {}", package_id))?;
+    let tx_digest = match resp.data.and_then(|d| d.previous_transaction) {
+        Some(d) => d,
+        None => return Ok(None),
+    };
+
+    let tx = rpc_call("get_transaction_with_options(object_changes)", || {
+        jsonrpc.read_api().get_transaction_with_options(
+            tx_digest,
+            sui_sdk::rpc_types::SuiTransactionBlockResponseOptions::new().with_object_changes(),
+        )
+    })
+    .await
+    .with_context(|| format!("fetch transaction {}", tx_digest))?;
+    let tx_value = serde_json::to_value(&tx)?;
+    let cap_id = tx_value
+        .get("objectChanges")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .find(|change| {
+            change
+                .get("objectType")
+                .and_then(Value::as_str)
+                .is_some_and(|t| t.ends_with("::package::UpgradeCap"))
+        })
+        .and_then(|change| change.get("objectId"))
+        .and_then(Value::as_str);
+    cap_id.map(object_id_from_hex_str).transpose()
+}
+
+/// Read the current policy of the package's `UpgradeCap` (see `find_upgrade_cap_via_rpc`).
+/// A cap that no longer exists was destroyed by `make_immutable` (or wrapped, which this
+/// lookup cannot tell apart).
+pub async fn fetch_upgrade_policy_via_rpc(
+    client: Arc<RpcClient>,
+    package_id: ObjectID,
+) -> Result<Option<String>> {
+    let jsonrpc = client.jsonrpc("upgrade policy lookup")?;
+    let cap_oid = match find_upgrade_cap_via_rpc(jsonrpc, package_id).await? {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+
+    let cap = rpc_call("get_object_with_options(upgrade_cap)", || {
+        jsonrpc.read_api().get_object_with_options(
+            cap_oid,
+            sui_sdk::rpc_types::SuiObjectDataOptions::new().with_content(),
+        )
+    })
+    .await
+    .with_context(|| format!("fetch upgrade cap {}", cap_oid))?;
+    let cap_value = serde_json::to_value(&cap)?;
+    if cap_value.get("data").is_none_or(Value::is_null) {
+        return Ok(Some("immutable".to_string()));
+    }
+    let policy = cap_value
+        .pointer("/data/content/fields/policy")
+        .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+        .ok_or_else(|| anyhow!("upgrade cap {} has no policy field", cap_oid))?;
+    Ok(Some(upgrade_policy_name(policy)))
+}
+
+/// Every on-chain version of the package `package_id` belongs to, oldest first: the
+/// original id (the self address of its modules), then the package published by each
+/// transaction that used its `UpgradeCap`. Works from any version in the chain and keeps
+/// working after the cap is destroyed. Packages without a cap yield just their original id.
+pub async fn resolve_upgrade_chain(
+    client: Arc<RpcClient>,
+    package_id: &str,
+) -> Result<Vec<String>> {
+    let jsonrpc = client.jsonrpc("upgrade chain lookup")?;
+    let oid = object_id_from_hex_str(package_id)
+        .map_err(|e| anyhow!("invalid package id {}: {}", package_id, e))?;
+
+    let modules = fetch_compiled_modules_via_rpc(Arc::clone(&client), oid)
+        .await?
+        .modules;
+    let original = modules
+        .first()
+        .map(|m| m.self_id().address().to_hex_literal())
+        .ok_or_else(|| anyhow!("package {} has no modules", package_id))?;
+    let mut chain = vec![normalize_package_id(&original)];
+
+    let Some(cap_oid) = find_upgrade_cap_via_rpc(jsonrpc, oid).await? else {
+        return Ok(chain);
+    };
+
+    let query = sui_sdk::rpc_types::SuiTransactionBlockResponseQuery::new(
+        Some(sui_sdk::rpc_types::TransactionFilter::InputObject(cap_oid)),
+        Some(sui_sdk::rpc_types::SuiTransactionBlockResponseOptions::new().with_object_changes()),
+    );
+    let mut cursor = None;
+    loop {
+        let page = rpc_call("query_transaction_blocks(upgrade_cap)", || {
+            jsonrpc
+                .read_api()
+                .query_transaction_blocks(query.clone(), cursor, None, false)
+        })
+        .await
+        .with_context(|| format!("query transactions using upgrade cap {}", cap_oid))?;
+        for tx in &page.data {
+            let tx_value = serde_json::to_value(tx)?;
+            let published = tx_value
+                .get("objectChanges")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(|change| change.get("type").and_then(Value::as_str) == Some("published"))
+                .filter_map(|change| change.get("packageId").and_then(Value::as_str))
+                .map(normalize_package_id);
+            for id in published {
+                if !chain.contains(&id) {
+                    chain.push(id);
+                }
+            }
+        }
+        if !page.has_next_page || page.next_cursor.is_none() {
+            break;
+        }
+        cursor = page.next_cursor;
+    }
+
+    if !chain.contains(&normalize_package_id(package_id)) {
+        tracing::warn!(%package_id, "package not found in its own upgrade chain");
+    }
+    Ok(chain)
+}
+
+pub fn object_id_from_hex_str(id: &str) -> anyhow::Result<ObjectID> {
+    ObjectID::from_str(id).with_context(|| format!("invalid object id: {id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transient_message_matches_whole_status_tokens_only() {
+        assert!(is_transient_rpc_message(
+            "HTTP status server error (502 Bad Gateway)"
+        ));
+        assert!(is_transient_rpc_message("Request rejected `429`"));
+        assert!(is_transient_rpc_message(
+            "Networking or low-level protocol error: eof"
+        ));
+        assert!(!is_transient_rpc_message("object 0x5002 not found"));
+        assert!(!is_transient_rpc_message(
+            "response body exceeded 5000 bytes"
+        ));
+        assert!(!is_transient_rpc_message(
+            "invalid params: connection field missing"
+        ));
+    }
+
+    #[test]
+    fn rpc_timeout_is_transient_through_context() {
+        let e = anyhow::Error::new(RpcTimeout {
+            what: "get_object".to_string(),
+            limit: Duration::from_secs(1),
+        })
+        .context("fetch package");
+        assert!(is_transient_rpc_error(&e));
+        assert!(!is_transient_rpc_error(&anyhow!("object not found")));
+    }
+}